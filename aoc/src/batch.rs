@@ -0,0 +1,284 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// One puzzle input found under `DIR/dayN/NAME.input`, with the answers from
+/// `DIR/dayN/NAME.expected` (one per line, part one first) if that exists.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Case {
+    pub day: u8,
+    pub input: PathBuf,
+    pub expected: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass(Vec<String>),
+    Fail(Vec<String>, Vec<Mismatch>),
+    Unchecked(Vec<String>),
+    Panic(String),
+    Error(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: usize,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "part {}: expected {}, got {}",
+                self.part, self.expected, actual
+            ),
+            None => write!(
+                f,
+                "part {}: expected {}, got nothing",
+                self.part, self.expected
+            ),
+        }
+    }
+}
+
+pub struct Outcome<'a> {
+    pub case: &'a Case,
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+}
+
+impl Display for Outcome<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (label, detail) = match &self.verdict {
+            Verdict::Pass(answers) => ("PASS", answers.join(", ")),
+            Verdict::Fail(_, mismatches) => (
+                "FAIL",
+                mismatches
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+            Verdict::Unchecked(answers) => ("DONE", answers.join(", ")),
+            Verdict::Panic(message) => ("PANIC", message.clone()),
+            Verdict::Error(message) => ("ERROR", message.clone()),
+        };
+        write!(
+            f,
+            "{:<40} {:<6} {:>10.1?}  {}",
+            self.case.input.display(),
+            label,
+            self.elapsed,
+            detail
+        )
+    }
+}
+
+fn day_from_dir(name: &str) -> Option<u8> {
+    name.strip_prefix("day")?.parse().ok()
+}
+
+pub fn discover(dir: &Path) -> std::io::Result<Vec<Case>> {
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(day) = entry.file_name().to_str().and_then(day_from_dir) else {
+            continue;
+        };
+        for file in fs::read_dir(entry.path())? {
+            let input = file?.path();
+            if input.extension().is_some_and(|e| e == "input") {
+                let expected = fs::read_to_string(input.with_extension("expected"))
+                    .ok()
+                    .map(|s| s.lines().map(|l| l.trim().to_owned()).collect());
+                cases.push(Case {
+                    day,
+                    input,
+                    expected,
+                });
+            }
+        }
+    }
+    cases.sort_by(|a, b| (a.day, &a.input).cmp(&(b.day, &b.input)));
+    Ok(cases)
+}
+
+fn panic_message(stderr: &str) -> Option<String> {
+    let mut lines = stderr.lines().skip_while(|l| !l.contains("panicked at"));
+    let at = lines.next()?;
+    Some(match lines.next() {
+        Some(message) => format!("{} {}", at.trim_end_matches(':'), message),
+        None => at.to_owned(),
+    })
+}
+
+/// Decides the verdict for one run from what the day binary printed. The day
+/// binaries print one answer per line, part one first.
+pub fn judge(success: bool, stdout: &str, stderr: &str, expected: Option<&[String]>) -> Verdict {
+    if !success {
+        return match panic_message(stderr) {
+            Some(message) => Verdict::Panic(message),
+            None => Verdict::Error(stderr.trim().to_owned()),
+        };
+    }
+    let answers = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let Some(expected) = expected else {
+        return Verdict::Unchecked(answers);
+    };
+    let mismatches = expected
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !e.is_empty())
+        .filter(|(i, e)| answers.get(*i) != Some(e))
+        .map(|(i, e)| Mismatch {
+            part: i + 1,
+            expected: e.clone(),
+            actual: answers.get(i).cloned(),
+        })
+        .collect::<Vec<_>>();
+    match mismatches.is_empty() {
+        true => Verdict::Pass(answers),
+        false => Verdict::Fail(answers, mismatches),
+    }
+}
+
+pub fn run_case<'a>(bin_dir: &Path, case: &'a Case) -> Outcome<'a> {
+    let bin = bin_dir.join(format!("day{}{}", case.day, std::env::consts::EXE_SUFFIX));
+    let start = Instant::now();
+    // cached answers would make the timing meaningless and could hide a
+    // regression in code the cache key doesn't cover
+    let output = Command::new(&bin)
+        .arg(&case.input)
+        .arg("--no-cache")
+        .output();
+    let elapsed = start.elapsed();
+    let verdict = match output {
+        Ok(output) => judge(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+            case.expected.as_deref(),
+        ),
+        Err(e) => Verdict::Error(format!("could not run {}: {e}", bin.display())),
+    };
+    Outcome {
+        case,
+        verdict,
+        elapsed,
+    }
+}
+
+/// Runs every input under `dir` and prints one line per input. Returns false
+/// if any of them failed, panicked or could not be run.
+pub fn run(dir: &Path, bin_dir: &Path) -> bool {
+    let cases = match discover(dir) {
+        Ok(cases) => cases,
+        Err(e) => {
+            eprintln!("could not read {}: {e}", dir.display());
+            return false;
+        }
+    };
+    let mut ok = true;
+    for case in &cases {
+        let outcome = run_case(bin_dir, case);
+        ok &= matches!(outcome.verdict, Verdict::Pass(_) | Verdict::Unchecked(_));
+        println!("{outcome}");
+    }
+    println!("{} inputs", cases.len());
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn judge_answers() {
+        let expected = strings(&["4361", "467835"]);
+        assert_eq!(
+            judge(true, "4361\n467835\n", "", Some(&expected)),
+            Verdict::Pass(expected.clone())
+        );
+        assert_eq!(
+            judge(true, "4361\n", "", Some(&expected)),
+            Verdict::Fail(
+                strings(&["4361"]),
+                vec![Mismatch {
+                    part: 2,
+                    expected: "467835".into(),
+                    actual: None
+                }]
+            )
+        );
+        assert_eq!(
+            judge(true, "1\n2\n", "", None),
+            Verdict::Unchecked(strings(&["1", "2"]))
+        );
+    }
+
+    #[test]
+    fn judge_only_part_one() {
+        let expected = strings(&["4361"]);
+        assert_eq!(
+            judge(true, "4361\n0\n", "", Some(&expected)),
+            Verdict::Pass(strings(&["4361", "0"]))
+        );
+    }
+
+    #[test]
+    fn judge_panic() {
+        let stderr = "thread 'main' panicked at day3/src/main.rs:63:14:\nbyte index 12 is out of bounds\nnote: run with `RUST_BACKTRACE=1`";
+        assert_eq!(
+            judge(false, "", stderr, None),
+            Verdict::Panic(
+                "thread 'main' panicked at day3/src/main.rs:63:14 byte index 12 is out of bounds"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn discover_cases() {
+        let dir = std::env::temp_dir().join(format!("aoc-batch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("day3")).unwrap();
+        fs::create_dir_all(dir.join("notes")).unwrap();
+        fs::write(dir.join("day3/bob.input"), "").unwrap();
+        fs::write(dir.join("day3/alice.input"), "").unwrap();
+        fs::write(dir.join("day3/alice.expected"), "4361\n467835\n").unwrap();
+        fs::write(dir.join("day3/readme.md"), "").unwrap();
+
+        let cases = discover(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            cases,
+            [
+                Case {
+                    day: 3,
+                    input: dir.join("day3/alice.input"),
+                    expected: Some(strings(&["4361", "467835"])),
+                },
+                Case {
+                    day: 3,
+                    input: dir.join("day3/bob.input"),
+                    expected: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use crate::html::to_markdown;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+pub const YEAR: u16 = 2023;
+const USER_AGENT: &str = "github.com/seanaye/aoc_2023 fetch";
+
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>, session: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            session: session.into(),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    /// Reads the session cookie from `AOC_SESSION`. `AOC_BASE_URL` points the
+    /// client somewhere other than adventofcode.com, e.g. `aoc mock-server`.
+    pub fn from_env() -> Result<Self, String> {
+        let session = std::env::var("AOC_SESSION")
+            .map_err(|_| "AOC_SESSION must be set to your adventofcode.com session cookie")?;
+        let base_url = std::env::var("AOC_BASE_URL").unwrap_or_else(|_| BASE_URL.to_owned());
+        Ok(Self::new(base_url, session))
+    }
+
+    fn get(&self, path: &str) -> Result<String, String> {
+        let url = format!("{}/{YEAR}/day/{path}", self.base_url);
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(code, response) => format!(
+                    "{url} returned {code}: {}",
+                    response.into_string().unwrap_or_default().trim()
+                ),
+                e => format!("could not fetch {url}: {e}"),
+            })?;
+        response
+            .into_string()
+            .map_err(|e| format!("could not read {url}: {e}"))
+    }
+
+    pub fn input(&self, day: u8) -> Result<String, String> {
+        self.get(&format!("{day}/input"))
+    }
+
+    pub fn puzzle(&self, day: u8) -> Result<String, String> {
+        self.get(&day.to_string()).map(|html| to_markdown(&html))
+    }
+}
+
+/// Downloads `input` and `puzzle.md` into `root/dayN`, leaving files that are
+/// already there alone unless `force` is set. Returns the files written.
+pub fn fetch(client: &Client, root: &Path, day: u8, force: bool) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no day {day}, days run from 1 to 25"));
+    }
+    let dir = root.join(format!("day{day}"));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("could not create {}: {e}", dir.display()))?;
+
+    let mut written = Vec::new();
+    for (name, download) in [
+        (
+            "input",
+            Client::input as fn(&Client, u8) -> Result<String, String>,
+        ),
+        ("puzzle.md", Client::puzzle),
+    ] {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            continue;
+        }
+        let contents = download(client, day)?;
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("could not write {}: {e}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("aoc-fetch-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn fetch_from_mock_server() {
+        let url = mock::start("127.0.0.1:0", mock::fixtures_dir()).unwrap();
+        let client = Client::new(url, "test-session");
+        let root = temp_root("cache");
+
+        let written = fetch(&client, &root, 1, false).unwrap();
+        assert_eq!(
+            written,
+            [root.join("day1/input"), root.join("day1/puzzle.md")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("day1/input")).unwrap(),
+            include_str!("../fixtures/day1.input")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("day1/puzzle.md")).unwrap(),
+            include_str!("../../day1/puzzle.md")
+        );
+
+        // cached files are kept until forced
+        std::fs::write(root.join("day1/input"), "edited").unwrap();
+        assert!(fetch(&client, &root, 1, false).unwrap().is_empty());
+        assert_eq!(fetch(&client, &root, 1, true).unwrap().len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(root.join("day1/input")).unwrap(),
+            include_str!("../fixtures/day1.input")
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn fetch_errors() {
+        let url = mock::start("127.0.0.1:0", mock::fixtures_dir()).unwrap();
+        let root = temp_root("errors");
+
+        let err = Client::new(&url, "").input(1).unwrap_err();
+        assert!(
+            err.contains("returned 400: Puzzle inputs differ by user."),
+            "{err}"
+        );
+
+        let err = fetch(&Client::new(&url, "test-session"), &root, 2, false).unwrap_err();
+        assert!(err.contains("/2023/day/2/input returned 404"), "{err}");
+
+        for day in [0, 26] {
+            let err = fetch(&Client::new(&url, "test-session"), &root, day, false).unwrap_err();
+            assert_eq!(err, format!("there is no day {day}, days run from 1 to 25"));
+            assert!(!root.join(format!("day{day}")).exists());
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,248 @@
+/// Converts an Advent of Code puzzle page into the markdown flavour of the
+/// committed `puzzle.md` files. Only the handful of tags those pages use are
+/// understood, anything else is dropped and its text kept.
+pub fn to_markdown(html: &str) -> String {
+    let mut md = Markdown::default();
+    let mut rest = main_section(html);
+    while let Some(start) = rest.find('<') {
+        md.text(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        md.tag(&Tag::parse(&rest[start + 1..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    md.text(rest);
+    md.finish()
+}
+
+fn main_section(html: &str) -> &str {
+    let Some(start) = html.find("<main>") else {
+        return html;
+    };
+    let html = &html[start + "<main>".len()..];
+    match html.find("</main>") {
+        Some(end) => &html[..end],
+        None => html,
+    }
+}
+
+#[derive(Debug)]
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    href: Option<&'a str>,
+}
+
+impl<'a> Tag<'a> {
+    fn parse(s: &'a str) -> Self {
+        let (closing, s) = match s.strip_prefix('/') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let name = s
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let href = s.find("href=\"").and_then(|i| {
+            let value = &s[i + "href=\"".len()..];
+            Some(&value[..value.find('"')?])
+        });
+        Tag {
+            name,
+            closing,
+            href,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Markdown {
+    out: String,
+    line: String,
+    pending_space: bool,
+    pre: bool,
+    code: usize,
+    skip: usize,
+    links: Vec<String>,
+}
+
+impl Markdown {
+    fn text(&mut self, s: &str) {
+        if self.skip > 0 || s.is_empty() {
+            return;
+        }
+        let s = decode_entities(s);
+        if self.pre {
+            self.line.push_str(&s);
+            return;
+        }
+        for c in s.chars() {
+            if c.is_whitespace() {
+                self.pending_space = true;
+                continue;
+            }
+            self.flush_space();
+            if self.code == 0 && matches!(c, '*' | '>') {
+                self.line.push('\\');
+            }
+            self.line.push(c);
+        }
+    }
+
+    fn flush_space(&mut self) {
+        if self.pending_space && !self.line.is_empty() {
+            self.line.push(' ');
+        }
+        self.pending_space = false;
+    }
+
+    fn inline(&mut self, s: &str) {
+        self.flush_space();
+        self.line.push_str(s);
+    }
+
+    fn take_line(&mut self) -> String {
+        self.pending_space = false;
+        let line = std::mem::take(&mut self.line);
+        match line.starts_with('-') {
+            true => format!("\\{line}"),
+            false => line,
+        }
+    }
+
+    fn block(&mut self, end: &str) {
+        let line = self.take_line();
+        if !line.is_empty() {
+            self.out.push_str(&line);
+            self.out.push_str(end);
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        match (tag.name.as_str(), tag.closing) {
+            ("script" | "style" | "head", false) => self.skip += 1,
+            ("script" | "style" | "head", true) => self.skip = self.skip.saturating_sub(1),
+            _ if self.skip > 0 => (),
+            ("pre", false) => {
+                self.block("\n\n");
+                self.pre = true;
+            }
+            ("pre", true) => {
+                let code = std::mem::take(&mut self.line);
+                self.out.push_str(&format!("```\n{code}\n```\n\n"));
+                self.pre = false;
+            }
+            (_, _) if self.pre => (),
+            ("em", _) => match tag.closing {
+                true => self.line.push('*'),
+                false => self.inline("*"),
+            },
+            ("code", false) => {
+                self.inline("`");
+                self.code += 1;
+            }
+            ("code", true) => {
+                self.line.push('`');
+                self.code = self.code.saturating_sub(1);
+            }
+            ("a", false) => {
+                self.links
+                    .push(decode_entities(tag.href.unwrap_or_default()));
+                self.inline("[");
+            }
+            ("a", true) => {
+                let href = self.links.pop().unwrap_or_default();
+                self.line.push_str(&format!("]({href})"));
+            }
+            ("br", _) => self.pending_space = true,
+            ("h2", true) => self.block("\n----------\n\n"),
+            ("li", false) => self.block("\n"),
+            ("li", true) => {
+                let line = self.take_line();
+                self.out.push_str(&format!("* {line}\n"));
+            }
+            ("ul", true) => {
+                self.block("\n");
+                self.out.push('\n');
+            }
+            ("p" | "h2" | "ul" | "article" | "main" | "form" | "div", _) => self.block("\n\n"),
+            _ => (),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.block("\n\n");
+        self.out.truncate(self.out.trim_end().len());
+        self.out
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                n => {
+                    let n = n.strip_prefix('#')?;
+                    let code = match n.strip_prefix('x') {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => n.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_committed_puzzle() {
+        assert_eq!(
+            to_markdown(include_str!("../fixtures/day1.html")),
+            include_str!("../../day1/puzzle.md")
+        );
+    }
+
+    #[test]
+    fn inline_markup() {
+        assert_eq!(
+            to_markdown("<p>Produces <code><em>6440</em></code>, a&lt;b -&gt; 2 * 3</p>"),
+            "Produces `*6440*`, a<b -\\> 2 \\* 3"
+        );
+        assert_eq!(
+            to_markdown(
+                "<ul>\n<li>one <em>two</em></li>\n<li><code>x</code></li>\n</ul><p>after</p>"
+            ),
+            "* one *two*\n* `x`\n\nafter"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+mod batch;
+mod fetch;
+mod html;
+mod mock;
+
+const USAGE: &str = "usage:
+  aoc batch <DIR>
+  aoc fetch <DAY> [--force]
+  aoc mock-server [ADDR]";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let ok = match args.as_slice() {
+        ["batch", dir] => batch::run(&PathBuf::from(dir), &bin_dir()),
+        ["fetch", day] => run_fetch(day, false),
+        ["fetch", day, "--force"] | ["fetch", "--force", day] => run_fetch(day, true),
+        ["mock-server"] => run_mock_server("127.0.0.1:8023"),
+        ["mock-server", addr] => run_mock_server(addr),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2)
+        }
+    };
+    if !ok {
+        std::process::exit(1)
+    }
+}
+
+/// The day binaries are built next to this one by `cargo build --workspace`.
+fn bin_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("could not locate the aoc binary");
+    exe.parent().unwrap().to_owned()
+}
+
+fn run_fetch(day: &str, force: bool) -> bool {
+    let Ok(day @ 1..=25) = day.parse::<u8>() else {
+        eprintln!("invalid day {day}, days run from 1 to 25");
+        return false;
+    };
+    let result = fetch::Client::from_env()
+        .and_then(|client| fetch::fetch(&client, &PathBuf::from("."), day, force));
+    match result {
+        Ok(written) if written.is_empty() => {
+            println!("day{day} is already cached, use --force to download again");
+            true
+        }
+        Ok(written) => {
+            for path in written {
+                println!("wrote {}", path.display());
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
+
+fn run_mock_server(addr: &str) -> bool {
+    match mock::start(addr, mock::fixtures_dir()) {
+        Ok(url) => {
+            println!("serving {} on {url}", mock::fixtures_dir().display());
+            loop {
+                std::thread::park();
+            }
+        }
+        Err(e) => {
+            eprintln!("could not listen on {addr}: {e}");
+            false
+        }
+    }
+}
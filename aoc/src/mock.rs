@@ -0,0 +1,74 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+};
+
+/// Where the fixture pages served by [`start`] live.
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// Starts a local stand-in for adventofcode.com on `addr` (use port 0 for any
+/// free port) and returns its base url. It answers the two requests `fetch`
+/// makes from files in `fixtures`:
+///
+/// * `/{year}/day/{n}` with `day{n}.html`
+/// * `/{year}/day/{n}/input` with `day{n}.input`, only with a session cookie
+pub fn start(addr: &str, fixtures: PathBuf) -> std::io::Result<String> {
+    let listener = TcpListener::bind(addr)?;
+    let url = format!("http://{}", listener.local_addr()?);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = handle(stream, &fixtures) {
+                eprintln!("mock server: {e}");
+            }
+        }
+    });
+    Ok(url)
+}
+
+fn handle(mut stream: TcpStream, fixtures: &Path) -> std::io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut has_session = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            has_session |= name.eq_ignore_ascii_case("cookie")
+                && value.split(';').any(|c| {
+                    c.trim()
+                        .strip_prefix("session=")
+                        .is_some_and(|s| !s.is_empty())
+                });
+        }
+    }
+
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    let (status, body) = match segments.as_slice() {
+        [_, "day", n] => read_fixture(fixtures, &format!("day{n}.html")),
+        [_, "day", _, "input"] if !has_session => (
+            "400 Bad Request",
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n".into(),
+        ),
+        [_, "day", n, "input"] => read_fixture(fixtures, &format!("day{n}.input")),
+        _ => ("404 Not Found", "404 Not Found\n".into()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn read_fixture(fixtures: &Path, name: &str) -> (&'static str, String) {
+    match std::fs::read_to_string(fixtures.join(name)) {
+        Ok(body) => ("200 OK", body),
+        Err(_) => ("404 Not Found", "404 Not Found\n".into()),
+    }
+}
@@ -0,0 +1,179 @@
+use std::{fmt::Display, path::PathBuf};
+
+/// On-disk store of answers keyed by day, part, a hash of the input bytes and
+/// a hash of the solver source together with this crate's version and source.
+/// Editing the input, the solver or the shared helpers misses the cache, so
+/// slow parts only run again when they could give a new answer.
+///
+/// Answers live in `$AOC_CACHE_DIR`, falling back to `$XDG_CACHE_HOME/aoc_2023`
+/// and then `~/.cache/aoc_2023`.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: Option<PathBuf>,
+    day: u8,
+    version: u64,
+    read: bool,
+}
+
+impl Cache {
+    /// `source` is the solver's own source, usually `include_str!("main.rs")`.
+    pub fn new(day: u8, source: &str) -> Self {
+        Self::at(default_dir(), day, source)
+    }
+
+    pub fn at(dir: Option<PathBuf>, day: u8, source: &str) -> Self {
+        Self {
+            dir,
+            day,
+            version: fnv1a([STAMP, source].concat().as_bytes()),
+            read: true,
+        }
+    }
+
+    /// Settings that change the answers, like a flag choosing another
+    /// vocabulary, so each choice gets its own entries.
+    pub fn keyed(mut self, settings: &str) -> Self {
+        self.version = fnv1a(format!("{:016x}{settings}", self.version).as_bytes());
+        self
+    }
+
+    /// Always recompute, still storing the fresh answer for the next run.
+    pub fn refresh(mut self) -> Self {
+        self.read = false;
+        self
+    }
+
+    fn path(&self, part: u8, input: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("day{}", self.day)).join(format!(
+            "part{part}-{:016x}-{:016x}",
+            fnv1a(input.as_bytes()),
+            self.version
+        )))
+    }
+
+    pub fn get_or_solve<T: Display>(
+        &self,
+        part: u8,
+        input: &str,
+        solve: impl FnOnce(&str) -> T,
+    ) -> String {
+        let path = self.path(part, input);
+        if let Some(answer) = path
+            .as_ref()
+            .filter(|_| self.read)
+            .and_then(|p| std::fs::read_to_string(p).ok())
+        {
+            return answer;
+        }
+
+        let answer = solve(input).to_string();
+        if let Some(path) = path {
+            let written = std::fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| std::fs::write(&path, &answer));
+            if let Err(e) = written {
+                eprintln!("could not cache answer in {}: {e}", path.display());
+            }
+        }
+        answer
+    }
+}
+
+// everything in this crate a solver might lean on, e.g. `input::normalize`
+const STAMP: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    include_str!("cache.rs"),
+    include_str!("cli.rs"),
+    include_str!("explain.rs"),
+    include_str!("input.rs"),
+    include_str!("lib.rs"),
+);
+
+fn default_dir() -> Option<PathBuf> {
+    let var = |name| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    var("AOC_CACHE_DIR")
+        .or_else(|| var("XDG_CACHE_HOME").map(|p| p.join("aoc_2023")))
+        .or_else(|| var("HOME").map(|p| p.join(".cache").join("aoc_2023")))
+}
+
+/// FNV-1a, used instead of `DefaultHasher` because its output has to stay the
+/// same across toolchains for old cache entries to be found.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn fnv1a_reference() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn cache_hits_and_misses() {
+        let dir = std::env::temp_dir().join(format!("aoc-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let calls = Cell::new(0);
+        let solve = |s: &str| {
+            calls.set(calls.get() + 1);
+            s.len()
+        };
+
+        let cache = Cache::at(Some(dir.clone()), 5, "fn part_two() {}");
+        assert_eq!(cache.get_or_solve(2, "abc", solve), "3");
+        assert_eq!(cache.get_or_solve(2, "abc", solve), "3");
+        assert_eq!(calls.get(), 1);
+
+        // other part, other input
+        cache.get_or_solve(1, "abc", solve);
+        cache.get_or_solve(2, "abcd", solve);
+        assert_eq!(calls.get(), 3);
+
+        // solver changed
+        let cache = Cache::at(Some(dir.clone()), 5, "fn part_two() { faster }");
+        cache.get_or_solve(2, "abc", solve);
+        assert_eq!(calls.get(), 4);
+
+        // other settings
+        let keyed = || Cache::at(Some(dir.clone()), 5, "fn part_two() {}").keyed("german");
+        keyed().get_or_solve(2, "abc", solve);
+        keyed().get_or_solve(2, "abc", solve);
+        assert_eq!(calls.get(), 5);
+
+        // --no-cache recomputes and stores the fresh answer
+        let cache = Cache::at(Some(dir.clone()), 5, "fn part_two() {}");
+        std::fs::write(cache.path(2, "abc").unwrap(), "stale").unwrap();
+        assert_eq!(cache.get_or_solve(2, "abc", solve), "stale");
+        assert_eq!(cache.refresh().get_or_solve(2, "abc", solve), "3");
+        assert_eq!(calls.get(), 6);
+        let cache = Cache::at(Some(dir.clone()), 5, "fn part_two() {}");
+        assert_eq!(cache.get_or_solve(2, "abc", solve), "3");
+        assert_eq!(calls.get(), 6);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn version_covers_common() {
+        let cache = Cache::at(None, 5, "fn part_two() {}");
+        assert_ne!(cache.version, fnv1a(b"fn part_two() {}"));
+        assert!(STAMP.contains("pub fn normalize"));
+    }
+
+    #[test]
+    fn no_cache_dir() {
+        let cache = Cache::at(None, 5, "");
+        assert_eq!(cache.get_or_solve(1, "abc", |s| s.len()), "3");
+    }
+}
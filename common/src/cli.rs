@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+
+use crate::{cache::Cache, explain::Format};
+
+/// Command line flags shared by every day binary.
+///
+/// ```text
+/// dayN [INPUT] [--explain[=text|json]] [--no-cache]
+/// ```
+///
+/// A day can take flags of its own through [`Args::parse_with`]. `--explain`
+/// is only accepted by days that list [`EXPLAIN`] among them, as only those
+/// have a trace to print.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub input: Option<PathBuf>,
+    pub explain: Option<Format>,
+    pub no_cache: bool,
+}
+
+/// Passed to [`Args::parse_with`] by days that can print a trace. The flag
+/// still ends up in [`Args::explain`] rather than being handed back.
+pub const EXPLAIN: &str = "--explain";
+
+impl Args {
+    pub fn parse() -> Self {
+        Self::parse_with(&[]).0
+    }
+
+    /// Like `parse`, but flags starting with one of `own` are handed back for
+    /// the day to interpret instead of being rejected.
+    pub fn parse_with(own: &[&str]) -> (Self, Vec<String>) {
+        match Self::from_args_with(std::env::args().skip(1), own) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2)
+            }
+        }
+    }
+
+    pub fn from_args(iter: impl IntoIterator<Item = impl Into<String>>) -> Result<Self, String> {
+        Self::from_args_with(iter, &[]).map(|(args, _)| args)
+    }
+
+    pub fn from_args_with(
+        iter: impl IntoIterator<Item = impl Into<String>>,
+        own: &[&str],
+    ) -> Result<(Self, Vec<String>), String> {
+        let mut args = Args::default();
+        let mut flags = vec![];
+        for arg in iter {
+            let arg = arg.into();
+            match arg.as_str() {
+                x if x.starts_with(EXPLAIN) && !own.contains(&EXPLAIN) => {
+                    return Err(format!("{x} isn't supported, this day has no trace"))
+                }
+                "--explain" | "--explain=text" => args.explain = Some(Format::Text),
+                "--explain=json" => args.explain = Some(Format::Json),
+                x if own
+                    .iter()
+                    .any(|flag| *flag != EXPLAIN && x.starts_with(flag)) =>
+                {
+                    flags.push(arg)
+                }
+                "--no-cache" => args.no_cache = true,
+                x if x.starts_with("--") => return Err(format!("unknown flag {x}")),
+                _ if args.input.is_some() => return Err(format!("unexpected argument {arg}")),
+                _ => args.input = Some(arg.into()),
+            }
+        }
+        Ok((args, flags))
+    }
+
+    /// Reads the input file given on the command line, falling back to the
+    /// input bundled with the binary.
+    pub fn read_input(&self, bundled: &str) -> String {
+        match &self.input {
+            Some(path) => std::fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display())),
+            None => bundled.to_owned(),
+        }
+    }
+
+    /// Prints an answer to stdout, or to stderr when explaining so stdout
+    /// holds nothing but the trace.
+    pub fn answer(&self, answer: impl std::fmt::Display) {
+        match self.explain {
+            Some(_) => eprintln!("{answer}"),
+            None => println!("{answer}"),
+        }
+    }
+
+    /// The answer cache for this run, see [`Cache::new`].
+    pub fn cache(&self, day: u8, source: &str) -> Cache {
+        match self.no_cache {
+            true => Cache::new(day, source).refresh(),
+            false => Cache::new(day, source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_flags() {
+        assert_eq!(Args::from_args(Vec::<String>::new()), Ok(Args::default()));
+        assert_eq!(
+            Args::from_args_with(["other.input", "--explain=json"], &[EXPLAIN]),
+            Ok((
+                Args {
+                    input: Some("other.input".into()),
+                    explain: Some(Format::Json),
+                    no_cache: false,
+                },
+                vec![]
+            ))
+        );
+        assert_eq!(
+            Args::from_args_with(["--explain"], &[EXPLAIN])
+                .unwrap()
+                .0
+                .explain,
+            Some(Format::Text)
+        );
+        assert!(Args::from_args(["--explain"]).is_err());
+        assert!(Args::from_args_with(["--explain=json"], &["--lines"]).is_err());
+        assert!(Args::from_args_with(["--explain=xml"], &[EXPLAIN]).is_err());
+        assert!(Args::from_args(["--no-cache"]).unwrap().no_cache);
+        assert!(Args::from_args(["--nope"]).is_err());
+        assert!(Args::from_args(["a", "b"]).is_err());
+    }
+
+    #[test]
+    fn parse_own_flags() {
+        let (args, flags) = Args::from_args_with(
+            ["--lines", "x.input", "--provenance=csv"],
+            &["--lines", "--provenance"],
+        )
+        .unwrap();
+        assert_eq!(args.input, Some("x.input".into()));
+        assert_eq!(flags, ["--lines", "--provenance=csv"]);
+        assert!(Args::from_args_with(["--lines"], &["--provenance"]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+/// A record of the decisions a solver made on the way to its answer, one
+/// [`Step`] per input item. Printed with `--explain` instead of reaching for
+/// `dbg!` when an answer comes out wrong.
+#[derive(Debug, Default)]
+pub struct Trace {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug)]
+pub struct Step {
+    part: u8,
+    item: String,
+    verdict: String,
+    details: Vec<(String, String)>,
+}
+
+impl Step {
+    pub fn part(&self) -> u8 {
+        self.part
+    }
+
+    pub fn item(&self) -> &str {
+        &self.item
+    }
+
+    pub fn verdict(&self) -> &str {
+        &self.verdict
+    }
+
+    pub fn details(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.details.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn detail(&mut self, key: impl Into<String>, value: impl ToString) -> &mut Self {
+        self.details.push((key.into(), value.to_string()));
+        self
+    }
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn step(&mut self, part: u8, item: impl Into<String>, verdict: impl ToString) -> &mut Step {
+        self.steps.push(Step {
+            part,
+            item: item.into(),
+            verdict: verdict.to_string(),
+            details: Vec::new(),
+        });
+        self.steps.last_mut().unwrap()
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = &Step> {
+        self.steps.iter()
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text => self.to_text(),
+            Format::Json => self.to_json(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for step in &self.steps {
+            let _ = writeln!(out, "[part {}] {}: {}", step.part, step.item, step.verdict);
+            for (key, value) in &step.details {
+                let _ = writeln!(out, "    {key}: {value}");
+            }
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let details = step
+                    .details
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", json_str(key), json_str(value)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(
+                    "{{\"part\":{},\"item\":{},\"verdict\":{},\"details\":{{{}}}}}",
+                    step.part,
+                    json_str(&step.item),
+                    json_str(&step.verdict),
+                    details
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("[{steps}]")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Trace {
+        let mut trace = Trace::new();
+        trace
+            .step(1, "Game 3", "impossible")
+            .detail("red", "20 > 12");
+        trace.step(2, "Game \"4\"", 36);
+        trace
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            example().to_text(),
+            "[part 1] Game 3: impossible\n    red: 20 > 12\n[part 2] Game \"4\": 36\n"
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            example().to_json(),
+            r#"[{"part":1,"item":"Game 3","verdict":"impossible","details":{"red":"20 > 12"}},{"part":2,"item":"Game \"4\"","verdict":"36","details":{}}]"#
+        );
+    }
+}
@@ -0,0 +1,56 @@
+const BOM: char = '\u{feff}';
+
+/// Rewrites raw puzzle input into the shape every parser expects: no byte
+/// order mark, `\n` line endings, no trailing whitespace on any line and no
+/// trailing blank lines. Leading whitespace and blank lines between blocks are
+/// left alone since some days depend on them.
+pub fn normalize(s: &str) -> String {
+    let s = s.strip_prefix(BOM).unwrap_or(s);
+    let mut out = s
+        .split('\n')
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// The same input written out with every combination of the encodings we
+/// have been bitten by. Used by the day tests to check that each example
+/// gives the same answer however it was saved.
+pub fn variants(s: &str) -> Vec<String> {
+    let lf = normalize(s);
+    let crlf = lf.replace('\n', "\r\n");
+    let trailing_spaces = lf
+        .lines()
+        .map(|line| format!("{line}  "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    vec![
+        lf.clone(),
+        format!("{lf}\n"),
+        format!("{lf}\n\n"),
+        format!("{crlf}\r\n"),
+        trailing_spaces.clone(),
+        format!("{BOM}{lf}"),
+        format!("{BOM}{}\r\n", trailing_spaces.replace('\n', "\r\n")),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_variants() {
+        let s = "ab\n\ncd\n  ef";
+        for v in variants(s) {
+            assert_eq!(normalize(&v), s, "{v:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_leading_whitespace() {
+        assert_eq!(normalize("\r\n  a \r\nb\t\r\n\r\n"), "\n  a\nb");
+    }
+}
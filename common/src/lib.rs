@@ -0,0 +1,4 @@
+pub mod cache;
+pub mod cli;
+pub mod explain;
+pub mod input;
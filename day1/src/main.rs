@@ -1,179 +1,677 @@
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take, take_while},
-    character::is_alphabetic,
-    combinator::{map_res, value},
-    number::complete::u32,
-    IResult, Parser,
+use std::{
+    fs::File,
+    io::{self, BufRead, Write},
+    ops::Range,
+    str::FromStr,
 };
 
+use common::{cli::Args, input::normalize};
+
 const INPUT: &str = include_str!("../input");
-const RADIX: u32 = 10u32;
-
-fn part_one() -> u32 {
-    iter_lines()
-        .map(|line| {
-            let mut first: Option<u32> = None;
-            let mut last: Option<u32> = None;
-            for char in line.chars() {
-                match (char.is_ascii_digit(), first, last) {
-                    (true, None, None) => {
-                        first = char.to_digit(RADIX);
-                    }
-                    (true, Some(_), _) => {
-                        last = char.to_digit(RADIX);
-                    }
-                    _ => (),
+
+fn part_one(input: &str) -> u32 {
+    calibrate(input, &Vocabulary::default())
+}
+
+fn part_two(input: &str) -> u32 {
+    calibrate(input, &Vocabulary::english())
+}
+
+/// Sum of the calibration values of every line, reading digits and any
+/// spelled number in `vocabulary`.
+fn calibrate(input: &str, vocabulary: &Vocabulary) -> u32 {
+    let input = normalize(input);
+    let calibrator = vocabulary.calibrator();
+    iter_lines(&input)
+        .map(str::trim)
+        .map(|line| calibrator.calibration_value(line))
+        .sum()
+}
+
+fn part_one_reader(reader: impl BufRead) -> io::Result<u32> {
+    calibrate_reader(reader, &Vocabulary::default())
+}
+
+fn part_two_reader(reader: impl BufRead) -> io::Result<u32> {
+    calibrate_reader(reader, &Vocabulary::english())
+}
+
+/// Like `calibrate`, one line at a time so only the current line is held in
+/// memory. Line endings, a byte order mark and other whitespace never match a
+/// word so the lines need no normalizing.
+fn calibrate_reader(mut reader: impl BufRead, vocabulary: &Vocabulary) -> io::Result<u32> {
+    let calibrator = vocabulary.calibrator();
+    let mut line = Vec::new();
+    let mut sum = 0;
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        sum += calibrator.calibration_value(&line);
+        line.clear();
+    }
+    Ok(sum)
+}
+
+/// One line per input line: its number, the first, last and calibration
+/// values and the byte spans of the first and last tokens, or `-` if nothing
+/// matched.
+fn write_calibrations(
+    mut reader: impl BufRead,
+    vocabulary: &Vocabulary,
+    mut out: impl Write,
+) -> io::Result<()> {
+    let calibrator = vocabulary.calibrator();
+    let mut line = Vec::new();
+    let mut number = 1;
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        match calibrator.calibrate_line(&line) {
+            Some(c) => writeln!(
+                out,
+                "{number}: {} {} {} {:?} {:?}",
+                c.first.value,
+                c.last.value,
+                c.value,
+                c.first.span(),
+                c.last.span()
+            )?,
+            None => writeln!(out, "{number}: -")?,
+        }
+        line.clear();
+        number += 1;
+    }
+    Ok(())
+}
+
+fn digits<'a>() -> impl Iterator<Item = (&'a str, u32)> {
+    ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+        .into_iter()
+        .zip(0..)
+}
+
+/// Spelled out numbers and the digit each stands for. Plain digits are
+/// always recognised and don't need to be listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// `one` to `nine`, as in the puzzle.
+    fn english() -> Self {
+        include_str!("../vocabularies/english").parse().unwrap()
+    }
+
+    fn english_with_zero() -> Self {
+        include_str!("../vocabularies/english_zero")
+            .parse()
+            .unwrap()
+    }
+
+    fn german() -> Self {
+        include_str!("../vocabularies/german").parse().unwrap()
+    }
+
+    fn french() -> Self {
+        include_str!("../vocabularies/french").parse().unwrap()
+    }
+
+    fn load(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .parse()
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// One of the bundled vocabularies by name, otherwise a file of them.
+    fn named(name: &str) -> Result<Self, String> {
+        match name {
+            "english" => Ok(Self::english()),
+            "english_zero" => Ok(Self::english_with_zero()),
+            "german" => Ok(Self::german()),
+            "french" => Ok(Self::french()),
+            path => Self::load(path),
+        }
+    }
+
+    fn calibrator(&self) -> Calibrator {
+        Calibrator::new(digits().chain(self.words.iter().map(|(w, v)| (w.as_str(), *v))))
+    }
+}
+
+/// One `word value` pair per line, `#` starts a comment.
+impl FromStr for Vocabulary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = Vec::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line
+                .split_once(char::is_whitespace)
+                .and_then(|(word, value)| Some((word, value.trim().parse::<u32>().ok()?)));
+            match parsed {
+                Some((word, value)) if value < 10 => words.push((word.to_string(), value)),
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `word digit`, got `{}`",
+                        n + 1,
+                        line
+                    ))
                 }
             }
-            let pos1 = first.unwrap_or(0);
-            let out: u32 = pos1 * 10 + last.unwrap_or(pos1);
-            out
-        })
-        .sum()
+        }
+        Ok(Vocabulary { words })
+    }
 }
 
-fn get_str_nums() -> Vec<&'static str> {
-    let one = "one";
-    let two = "two";
-    let three = "three";
-    let four = "four";
-    let five = "five";
-    let six = "six";
-    let seven = "seven";
-    let eight = "eight";
-    let nine = "nine";
-    let zero = "zero";
-
-    vec![one, two, three, four, five, six, seven, eight, nine, zero]
-}
-
-fn word_to_num(s: &str) -> Option<u32> {
-    let out = match s {
-        "one" => Some(1u32),
-        "two" => Some(2u32),
-        "three" => Some(3u32),
-        "four" => Some(4u32),
-        "five" => Some(5u32),
-        "six" => Some(6u32),
-        "seven" => Some(7u32),
-        "eight" => Some(8u32),
-        "nine" => Some(9u32),
-        "zero" => Some(0u32),
-        _ => None,
-    };
-    dbg!(&out);
-    out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Match {
+    value: u32,
+    start: usize,
+    end: usize,
 }
 
-fn part_two() -> u32 {
-    iter_lines()
-        .map(str::trim)
-        .map(|line| {
-            let mut first: Option<u32> = None;
-            let mut last: Option<u32> = None;
-            for (i, char) in line.chars().enumerate() {
-                let is_digit = char.is_ascii_digit();
-
-                if !is_digit {
-                    match (search_slice(&line[i..]), first, last) {
-                        (Some(num), None, None) => {
-                            first = Some(num);
-                        }
-                        (Some(num), Some(_), _) => {
-                            last = Some(num);
-                        }
-                        _ => (),
+impl Match {
+    /// Byte offsets of the token within its line.
+    fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Calibration {
+    first: Match,
+    last: Match,
+    value: u32,
+}
+
+const ROOT: usize = 0;
+
+/// Aho-Corasick automaton over a vocabulary of digit spellings. Every
+/// occurrence is reported, including overlapping ones like the `one` and
+/// `eight` in `oneight`, from a single pass over the line.
+struct Matcher {
+    // full transition table, one row of 256 byte transitions per state
+    next: Vec<[usize; 256]>,
+    // the word ending at this state, if any: (length, value)
+    word: Vec<Option<(usize, u32)>>,
+    // nearest state on the failure chain that ends a word
+    output: Vec<Option<usize>>,
+    // length of the longest word
+    longest: usize,
+}
+
+impl Matcher {
+    fn new<W: AsRef<[u8]>>(vocabulary: impl IntoIterator<Item = (W, u32)>) -> Self {
+        let mut matcher = Matcher {
+            next: vec![[ROOT; 256]],
+            word: vec![None],
+            output: vec![None],
+            longest: 0,
+        };
+        // the trie, with missing transitions left pointing at the root
+        for (word, value) in vocabulary {
+            let word = word.as_ref();
+            matcher.longest = matcher.longest.max(word.len());
+            let mut state = ROOT;
+            for &b in word {
+                state = match matcher.next[state][b as usize] {
+                    ROOT => {
+                        matcher.next.push([ROOT; 256]);
+                        matcher.word.push(None);
+                        matcher.output.push(None);
+                        let new = matcher.next.len() - 1;
+                        matcher.next[state][b as usize] = new;
+                        new
                     }
-                } else {
-                    match (is_digit, first, last) {
-                        (true, None, None) => {
-                            first = char.to_digit(RADIX);
-                        }
-                        (true, Some(_), _) => {
-                            last = char.to_digit(RADIX);
-                        }
-                        _ => (),
+                    next => next,
+                };
+            }
+            matcher.word[state] = Some((word.len(), value));
+        }
+
+        // breadth first so every failure link is final before it is followed
+        let mut fail = vec![ROOT; matcher.next.len()];
+        let mut queue = matcher.next[ROOT]
+            .iter()
+            .copied()
+            .filter(|&s| s != ROOT)
+            .collect::<std::collections::VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            let f = fail[state];
+            matcher.output[state] = match matcher.word[f] {
+                Some(_) => Some(f),
+                None => matcher.output[f],
+            };
+            for b in 0..256 {
+                match matcher.next[state][b] {
+                    ROOT => matcher.next[state][b] = matcher.next[f][b],
+                    child => {
+                        fail[child] = matcher.next[f][b];
+                        queue.push_back(child);
                     }
                 }
             }
-            let pos1 = first.unwrap_or(0);
-            let out: u32 = pos1 * 10 + last.unwrap_or(pos1);
-            dbg!(&out);
-            out
-        })
-        .sum()
-}
+        }
+        matcher
+    }
 
-fn search_slice(s: &str) -> Option<u32> {
-    let nums = get_str_nums();
-    for num in nums {
-        for (i, c) in s.chars().enumerate() {
-            if i >= num.len() {
-                return word_to_num(num);
+    fn matches_at(&self, state: usize, end: usize) -> impl Iterator<Item = Match> + '_ {
+        let here = self.word[state].map(|_| state);
+        std::iter::successors(here.or(self.output[state]), |&s| self.output[s]).map(move |s| {
+            let (len, value) = self.word[s].unwrap();
+            Match {
+                value,
+                start: end - len,
+                end,
             }
-            let this_char = num.chars().nth(i).unwrap_or('.');
-            dbg!(this_char, c);
-            if this_char != c {
+        })
+    }
+
+    /// Every occurrence in `line`, ordered by where it ends.
+    fn find_iter<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Match> + 'a {
+        line.bytes()
+            .enumerate()
+            .scan(ROOT, |state, (i, b)| {
+                *state = self.next[*state][b as usize];
+                Some((*state, i + 1))
+            })
+            .flat_map(|(state, end)| self.matches_at(state, end))
+    }
+
+    /// The occurrence that starts first in `bytes`. Scanning stops once no
+    /// word could still start before the best one found so far.
+    fn leftmost(&self, bytes: impl Iterator<Item = u8>) -> Option<Match> {
+        let mut state = ROOT;
+        let mut best: Option<Match> = None;
+        for (i, b) in bytes.enumerate() {
+            if best.is_some_and(|m| i >= m.start + self.longest) {
                 break;
             }
-            if i == s.len() - 1 && i == num.len() - 1 {
-                dbg!("here", num, s);
-                return word_to_num(num);
+            state = self.next[state][b as usize];
+            for m in self.matches_at(state, i + 1) {
+                // on a tie the shorter word, which was found first, wins
+                if best.is_none_or(|best| m.start < best.start) {
+                    best = Some(m);
+                }
             }
         }
+        best
+    }
+
+    /// The occurrences that start first and last in `line`, found by walking
+    /// the whole line.
+    #[allow(dead_code)]
+    fn first_last(&self, line: &str) -> Option<(Match, Match)> {
+        self.find_iter(line).fold(None, |acc, m| match acc {
+            None => Some((m, m)),
+            Some((first, last)) => Some((
+                match m.start < first.start {
+                    true => m,
+                    false => first,
+                },
+                match m.start > last.start {
+                    true => m,
+                    false => last,
+                },
+            )),
+        })
+    }
+}
+
+/// Finds the first value scanning forwards from the start of a line and the
+/// last scanning backwards from its end, so only the ends of a long line are
+/// read.
+struct Calibrator {
+    forward: Matcher,
+    // the same vocabulary spelled backwards
+    backward: Matcher,
+}
+
+impl Calibrator {
+    fn new<W: AsRef<[u8]>>(vocabulary: impl IntoIterator<Item = (W, u32)>) -> Self {
+        let words = vocabulary.into_iter().collect::<Vec<_>>();
+        let backward = words.iter().map(|(word, value)| {
+            let mut word = word.as_ref().to_vec();
+            word.reverse();
+            (word, *value)
+        });
+        Calibrator {
+            backward: Matcher::new(backward),
+            forward: Matcher::new(words),
+        }
+    }
+
+    fn first_last(&self, line: impl AsRef<[u8]>) -> Option<(Match, Match)> {
+        let line = line.as_ref();
+        let first = self.forward.leftmost(line.iter().copied())?;
+        // the first word to end in the reversed line is the last to start in
+        // the line itself; shorter words come last at a state
+        let mut state = ROOT;
+        let last = line.iter().rev().enumerate().find_map(|(i, &b)| {
+            state = self.backward.next[state][b as usize];
+            self.backward.matches_at(state, i + 1).last()
+        })?;
+        let last = Match {
+            value: last.value,
+            start: line.len() - last.end,
+            end: line.len() - last.start,
+        };
+        Some((first, last))
     }
-    None
-}
-
-// fn is_num(s: &str) -> Result<u32, ()> {
-//     match s {
-//         _ => Err(())
-//     }
-// }
-//
-//
-// fn is_num(s: &str) -> IResult<&str, u32> {
-//     alt((
-//         value(1u32, tag("one")),
-//         value(2u32, tag("two")),
-//         value(3u32, tag("three")),
-//         value(4u32, tag("four")),
-//         value(5u32, tag("five")),
-//         value(6u32, tag("six")),
-//         value(7u32, tag("seven")),
-//         value(8u32, tag("eight")),
-//         value(9u32, tag("nine")),
-//         value(0u32, tag("zero")),
-//     ))(s)
-// }
-
-// fn num_str(s: &str) -> IResult<(&str, u32), u8> {
-//     let out = (s);
-//     dbg!(out);
-//     todo!()
-// }
-
-// fn part_two() -> u32 {
-//     iter_lines().map(num_str);
-//     todo!()
-// }
 
+    /// The first and last values of `line` and where they were found, or
+    /// `None` when it has neither digits nor words.
+    fn calibrate_line(&self, line: impl AsRef<[u8]>) -> Option<Calibration> {
+        self.first_last(line).map(|(first, last)| Calibration {
+            first,
+            last,
+            value: first.value * 10 + last.value,
+        })
+    }
+
+    fn calibration_value(&self, line: impl AsRef<[u8]>) -> u32 {
+        self.calibrate_line(line).map_or(0, |c| c.value)
+    }
+}
+
+/// `--stream` reads the input a line at a time rather than all at once, and
+/// `--lines` prints every line's calibration with the part two words.
+/// `--vocabulary=` swaps the part two words for `english_zero`, `german`,
+/// `french` or the path of a file of `word digit` lines.
 fn main() {
-    dbg!(part_two());
-    // dbg!(part_one());
+    let (args, flags) = Args::parse_with(&["--stream", "--lines", "--vocabulary="]);
+    let mut vocabulary = None;
+    let mut mode = None;
+    for flag in &flags {
+        match flag.strip_prefix("--vocabulary=") {
+            Some(name) => {
+                vocabulary = Some(Vocabulary::named(name).unwrap_or_else(|e| {
+                    eprintln!("bad vocabulary {e}");
+                    std::process::exit(2)
+                }))
+            }
+            None => mode = Some(flag.as_str()),
+        }
+    }
+    let streamed = match mode {
+        None => None,
+        Some("--lines") => Some(write_calibrations(
+            open(&args),
+            vocabulary.as_ref().unwrap_or(&Vocabulary::english()),
+            io::stdout().lock(),
+        )),
+        Some("--stream") => Some(print_streamed(
+            &args,
+            vocabulary.as_ref(),
+            io::stdout().lock(),
+        )),
+        Some(flag) => {
+            eprintln!("unknown flag {flag}");
+            std::process::exit(2)
+        }
+    };
+    match streamed {
+        Some(Err(e)) if e.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("could not stream the input: {e}");
+            std::process::exit(1)
+        }
+        Some(_) => return,
+        None => {}
+    }
+    let input = args.read_input(INPUT);
+    let cache = args.cache(1, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    let answer = match &vocabulary {
+        Some(vocabulary) => {
+            cache
+                .keyed(&format!("{vocabulary:?}"))
+                .get_or_solve(2, &input, |input| calibrate(input, vocabulary))
+        }
+        None => cache.get_or_solve(2, &input, part_two),
+    };
+    println!("{answer}");
 }
 
-fn iter_lines() -> impl Iterator<Item = &'static str> {
-    INPUT.lines()
+fn print_streamed(
+    args: &Args,
+    vocabulary: Option<&Vocabulary>,
+    mut out: impl Write,
+) -> io::Result<()> {
+    writeln!(out, "{}", part_one_reader(open(args))?)?;
+    let two = match vocabulary {
+        Some(vocabulary) => calibrate_reader(open(args), vocabulary)?,
+        None => part_two_reader(open(args))?,
+    };
+    writeln!(out, "{two}")
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     #[test]
-//     fn test_main() {
-//         main();
-//     }
-// }
+/// The input file given on the command line, or the bundled input, read a
+/// buffer at a time.
+fn open(args: &Args) -> Box<dyn BufRead> {
+    match &args.input {
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(e) => panic!("could not read {}: {e}", path.display()),
+        },
+        None => Box::new(INPUT.as_bytes()),
+    }
+}
+
+fn iter_lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines()
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::variants;
+
+    #[test]
+    fn test_part_one() {
+        assert_eq!(part_one(include_str!("../example")), 142);
+    }
+
+    #[test]
+    fn test_part_two() {
+        assert_eq!(part_two(include_str!("../example2")), 281);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let calibrator = Vocabulary::english().calibrator();
+        assert_eq!(
+            calibrator.forward.find_iter("oneight").collect::<Vec<_>>(),
+            [
+                Match {
+                    value: 1,
+                    start: 0,
+                    end: 3
+                },
+                Match {
+                    value: 8,
+                    start: 2,
+                    end: 7
+                },
+            ]
+        );
+        assert_eq!(calibrator.calibration_value("oneight"), 18);
+        assert_eq!(calibrator.calibration_value("twone"), 21);
+        assert_eq!(calibrator.calibration_value("xtwonex"), 21);
+        assert_eq!(calibrator.calibration_value("eightwothree"), 83);
+        assert_eq!(calibrator.calibration_value("7"), 77);
+        assert_eq!(calibrator.calibration_value("abc"), 0);
+    }
+
+    #[test]
+    fn test_contained_words() {
+        // a word found inside a longer one still counts from where it starts
+        let matcher = Matcher::new([("abcd", 1), ("bc", 2), ("c", 3)]);
+        assert_eq!(
+            matcher.first_last("abcd").map(|(f, l)| (f.value, l.value)),
+            Some((1, 3))
+        );
+        assert_eq!(matcher.find_iter("abcd").count(), 3);
+        let calibrator = Calibrator::new([("abcd", 1), ("bc", 2), ("c", 3)]);
+        assert_eq!(calibrator.first_last("abcd"), matcher.first_last("abcd"));
+    }
+
+    #[test]
+    fn test_reader() {
+        for input in variants(include_str!("../example")) {
+            assert_eq!(part_one_reader(input.as_bytes()).unwrap(), 142);
+        }
+        for input in variants(include_str!("../example2")) {
+            assert_eq!(part_two_reader(input.as_bytes()).unwrap(), 281);
+        }
+        let reader = io::BufReader::with_capacity(16, INPUT.as_bytes());
+        assert_eq!(part_two_reader(reader).unwrap(), part_two(INPUT));
+        let reader = "fünf\r\nneun1".as_bytes();
+        assert_eq!(
+            calibrate_reader(reader, &Vocabulary::german()).unwrap(),
+            55 + 91
+        );
+    }
+
+    #[test]
+    fn test_write_calibrations() {
+        let mut out = vec![];
+        write_calibrations(
+            "xtwone3four\r\nabc\n7".as_bytes(),
+            &Vocabulary::english(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: 2 4 24 1..4 7..11\n2: -\n3: 7 7 77 0..1 0..1\n"
+        );
+    }
+
+    #[test]
+    fn test_calibrate_line() {
+        let calibrator = Vocabulary::english().calibrator();
+        let c = calibrator.calibrate_line("xtwone3four").unwrap();
+        assert_eq!((c.first.value, c.last.value, c.value), (2, 4, 24));
+        assert_eq!((c.first.span(), c.last.span()), (1..4, 7..11));
+        let c = calibrator.calibrate_line("abc7def").unwrap();
+        assert_eq!((c.first.span(), c.last.span(), c.value), (3..4, 3..4, 77));
+        // spans are in bytes, not chars
+        let c = Vocabulary::german()
+            .calibrator()
+            .calibrate_line("ä fünf")
+            .unwrap();
+        assert_eq!(c.first.span(), 3..8);
+        assert_eq!(calibrator.calibrate_line("abc"), None);
+    }
+
+    // a long line of noise with a few digits and words scattered through it
+    fn generate_line(seed: u64, len: usize) -> String {
+        let tokens = ["one", "two", "three", "seven", "eight", "nine", "4", "5"];
+        let mut state = seed;
+        let mut line = String::with_capacity(len);
+        while line.len() < len {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            match (state >> 33) % 64 {
+                0 => line.push_str(tokens[(state >> 40) as usize % tokens.len()]),
+                r => line.push((b'a' + (r % 26) as u8) as char),
+            }
+        }
+        line
+    }
+
+    #[test]
+    fn test_scan_from_both_ends() {
+        let calibrator = Vocabulary::english().calibrator();
+        for seed in 0..500 {
+            let line = generate_line(seed, seed as usize % 200);
+            assert_eq!(
+                calibrator.first_last(&line),
+                calibrator.forward.first_last(&line),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_scan_from_both_ends() {
+        let calibrator = Vocabulary::english().calibrator();
+        let lines = (0..8)
+            .map(|seed| generate_line(seed, 1 << 22))
+            .collect::<Vec<_>>();
+
+        let start = std::time::Instant::now();
+        let full = lines
+            .iter()
+            .map(|line| calibrator.forward.first_last(line))
+            .collect::<Vec<_>>();
+        let full_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let ends = lines
+            .iter()
+            .map(|line| calibrator.first_last(line))
+            .collect::<Vec<_>>();
+        let ends_time = start.elapsed();
+
+        assert_eq!(full, ends);
+        println!("whole line: {:?}, both ends: {:?}", full_time, ends_time);
+    }
+
+    #[test]
+    fn test_vocabularies() {
+        assert_eq!(calibrate("zero1nine", &Vocabulary::english()), 19);
+        assert_eq!(calibrate("zero1nine", &Vocabulary::english_with_zero()), 9);
+        assert_eq!(
+            calibrate("xfünfzweix\nachtundneunzig", &Vocabulary::german()),
+            52 + 89
+        );
+        assert_eq!(
+            calibrate("septhuitrois\nun", &Vocabulary::french()),
+            73 + 11
+        );
+        assert_eq!(calibrate("one\nuno", &Vocabulary::default()), 0);
+    }
+
+    #[test]
+    fn test_load_vocabulary() {
+        let vocabulary: Vocabulary = "# roman\ni 1\nv 5 # five\n\n".parse().unwrap();
+        assert_eq!(calibrate("xvi", &vocabulary), 51);
+        assert_eq!(calibrate("ii", &vocabulary), 11);
+        assert!("one".parse::<Vocabulary>().is_err());
+        assert!("ten 10".parse::<Vocabulary>().is_err());
+        assert!(Vocabulary::load("vocabularies/missing").is_err());
+        assert_eq!(
+            Vocabulary::load(concat!(env!("CARGO_MANIFEST_DIR"), "/vocabularies/german")),
+            Ok(Vocabulary::german())
+        );
+        assert_eq!(Vocabulary::named("french"), Ok(Vocabulary::french()));
+        assert_eq!(
+            Vocabulary::named("english_zero"),
+            Ok(Vocabulary::english_with_zero())
+        );
+        assert!(Vocabulary::named("klingon").is_err());
+    }
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(part_one(INPUT), 54450);
+        assert_eq!(part_two(INPUT), 54265);
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in variants(include_str!("../example")) {
+            assert_eq!(part_one(&input), 142);
+        }
+        for input in variants(include_str!("../example2")) {
+            assert_eq!(part_two(&input), 281);
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use std::{collections::HashSet, ops::Add, str::FromStr};
 
+use common::{cli::Args, input::normalize};
+
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(10, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -262,14 +267,14 @@ impl Iterator for Paths<'_> {
 }
 
 fn part_one(input: &str) -> usize {
-    let grid: Grid<Tile> = input.parse().unwrap();
+    let grid: Grid<Tile> = normalize(input).parse().unwrap();
 
     let (coord, direction) = grid.find_starts().next().unwrap();
     grid.paths(coord, direction).count() / 2
 }
 
 fn part_two(input: &str) -> usize {
-    let grid: Grid<Tile> = input.parse().unwrap();
+    let grid: Grid<Tile> = normalize(input).parse().unwrap();
 
     let (coord, direction) = grid.find_starts().next().unwrap();
     let points = grid.paths(coord, direction).collect::<Vec<_>>();
@@ -327,5 +332,15 @@ mod tests {
     fn part_two_test_two() {
         assert_eq!(part_two(include_str!("../test4")), 10)
     }
+
+    #[test]
+    fn input_variants() {
+        for input in common::input::variants(include_str!("../test2")) {
+            assert_eq!(part_one(&input), 8)
+        }
+        for input in common::input::variants(include_str!("../test4")) {
+            assert_eq!(part_two(&input), 10)
+        }
+    }
 }
 
@@ -4,11 +4,18 @@ use std::{
     str::FromStr,
 };
 
+use common::{cli::Args, input::normalize};
 use itertools::Itertools;
 
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input"), 10usize.pow(6)));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(11, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!(
+        "{}",
+        cache.get_or_solve(2, &input, |s| part_two(s, 10usize.pow(6)))
+    );
 }
 
 // enum Direction {
@@ -82,6 +89,7 @@ impl<T> Grid<T> {
         })
     }
 
+    #[allow(dead_code)]
     pub fn insert_row(&mut self, at: &usize, to_add: impl Iterator<Item = T>) {
         let new_row = to_add.take(self.width);
         let len = self.inner.len();
@@ -92,6 +100,7 @@ impl<T> Grid<T> {
         assert!(len + self.width == cur);
     }
 
+    #[allow(dead_code)]
     pub fn insert_col(&mut self, at: &usize, to_add: impl Iterator<Item = T>) {
         let new_col = to_add.take(self.height);
         let len = self.inner.len();
@@ -265,7 +274,7 @@ impl OffsetGrid {
 
 
 fn part_one(s: &str) -> u64 {
-    let grid = Grid::<Tile>::from_str(s).unwrap();
+    let grid = Grid::<Tile>::from_str(&normalize(s)).unwrap();
     let offset_grid = OffsetGrid::new(grid, 1);
     offset_grid.iter_with_offset()
         .filter(|(_, tile)| tile == &&Tile::Galaxy)
@@ -278,7 +287,7 @@ fn part_one(s: &str) -> u64 {
 }
 
 fn part_two(s: &str, size: usize) -> u64 {
-    let grid = Grid::<Tile>::from_str(s).unwrap();
+    let grid = Grid::<Tile>::from_str(&normalize(s)).unwrap();
     let offset_grid = OffsetGrid::new(grid, size - 1);
     offset_grid.iter_with_offset()
         .filter(|(_, tile)| tile == &&Tile::Galaxy)
@@ -313,5 +322,13 @@ mod tests {
     fn test_100_times_larger() {
         assert_eq!(part_two(include_str!("../test1"), 100), 8410)
     }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test1")) {
+            assert_eq!(part_one(&input), 374);
+            assert_eq!(part_two(&input, 10), 1030);
+        }
+    }
 }
 
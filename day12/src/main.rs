@@ -4,9 +4,21 @@ use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::sync::Mutex;
 
+use common::{
+    cli::{Args, EXPLAIN},
+    explain::Trace,
+    input::normalize,
+};
+
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let (args, _) = Args::parse_with(&[EXPLAIN]);
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(12, include_str!("main.rs"));
+    if let Some(format) = args.explain {
+        println!("{}", explain(&input).render(format));
+    }
+    args.answer(cache.get_or_solve(1, &input, part_one));
+    args.answer(cache.get_or_solve(2, &input, part_two));
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -206,6 +218,7 @@ impl<'a, 'b> RowSlice<'a, 'b> {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Decision {
     One(State),
@@ -215,6 +228,7 @@ enum Decision {
     Complete,
 }
 
+#[allow(dead_code)]
 #[derive(Clone)]
 struct DecisionNode<'a> {
     current: Vec<State>,
@@ -246,6 +260,7 @@ impl std::fmt::Debug for DecisionNode<'_> {
     }
 }
 
+#[allow(dead_code)]
 impl<'a> DecisionNode<'a> {
     fn empty(row: &'a Row) -> Self {
         Self {
@@ -312,10 +327,11 @@ impl<'a> DecisionNode<'a> {
     }
 }
 
-/// hashmap of counts of success
-/// from remaining tiles
-/// retrieve memo
+// hashmap of counts of success
+// from remaining tiles
+// retrieve memo
 
+#[allow(dead_code)]
 struct DecisionNodeIterator<'a> {
     to_visit: Vec<DecisionNode<'a>>,
     visited: HashMap<(&'a [Spring], usize), usize>,
@@ -351,6 +367,7 @@ impl<'a> Iterator for DecisionNodeIterator<'a> {
     }
 }
 
+#[allow(dead_code)]
 impl<'a> DecisionNodeIterator<'a> {
     fn new_from_row(row: &'a Row) -> Self {
         let first = DecisionNode::empty(row);
@@ -370,9 +387,10 @@ fn count_line_cache<'a>(r: &'a Row, cache: &Mutex<HashMap<(&'a [Spring], usize),
     RowSlice::from_row(r, cache).sum()
 }
 
+#[allow(dead_code)]
 fn count_line_old(r: &Row) -> usize {
     let iter = DecisionNodeIterator::new_from_row(r);
-    iter.filter(|(node, decision)| {
+    iter.filter(|(_node, decision)| {
         // if decision == &Decision::Complete {
         //     dbg!(node);
         // }
@@ -382,12 +400,17 @@ fn count_line_old(r: &Row) -> usize {
 }
 
 fn part_one(s: &str) -> usize {
-    s.lines().map(|l| l.parse().unwrap()).map(count_line).sum()
+    normalize(s)
+        .lines()
+        .map(|l| l.parse().unwrap())
+        .map(count_line)
+        .sum()
 }
 
 fn part_two(s: &str) -> usize {
     let cache = Mutex::new(HashMap::new());
-    s.lines()
+    normalize(s)
+        .lines()
         .map(|l| {
             let mut r = l.parse::<Row>().unwrap();
             r.expand(5);
@@ -399,6 +422,21 @@ fn part_two(s: &str) -> usize {
         .sum()
 }
 
+fn explain(s: &str) -> Trace {
+    let s = normalize(s);
+    let mut trace = Trace::new();
+    for (part, times) in [(1, 1), (2, 5)] {
+        for (i, l) in s.lines().enumerate() {
+            let mut r = l.parse::<Row>().unwrap();
+            r.expand(times);
+            trace
+                .step(part, format!("row {}", i + 1), count_line(r))
+                .detail("springs", l);
+        }
+    }
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -408,6 +446,42 @@ mod tests {
         assert_eq!(part_one(include_str!("../example")), 21)
     }
 
+    #[test]
+    fn test_explain() {
+        let trace = explain(include_str!("../example"));
+        let counts = trace
+            .steps()
+            .map(|step| (step.part(), step.verdict()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            [
+                (1, "1"),
+                (1, "4"),
+                (1, "1"),
+                (1, "1"),
+                (1, "4"),
+                (1, "10"),
+                (2, "1"),
+                (2, "16384"),
+                (2, "1"),
+                (2, "16"),
+                (2, "2500"),
+                (2, "506250"),
+            ]
+        );
+        assert!(trace
+            .to_text()
+            .starts_with("[part 1] row 1: 1\n    springs: ???.### 1,1,3\n"));
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../example")) {
+            assert_eq!(part_one(&input), 21)
+        }
+    }
+
     #[test]
     #[ignore]
     fn compare_old() {
@@ -585,4 +659,3 @@ mod tests {
         assert_eq!(part_one(include_str!("../input")), 7090)
     }
 }
-
@@ -1,13 +1,18 @@
 use std::{
-    cmp::{max, min},
+    cmp::min,
     fmt::Write,
     ops::{Add, Sub},
     str::FromStr,
 };
 
+use common::{cli::Args, input::normalize};
+
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(13, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 #[derive(PartialEq, Eq)]
@@ -24,6 +29,7 @@ impl<T> Grid<T> {
             .unwrap()
     }
 
+    #[allow(dead_code)]
     fn coord_from_index(&self, index: usize) -> Coord {
         Coord {
             x: index as i64 % self.width as i64,
@@ -31,6 +37,7 @@ impl<T> Grid<T> {
         }
     }
 
+    #[allow(dead_code)]
     pub fn iter_coord(&self) -> impl Iterator<Item = (Coord, &'_ T)> {
         self.inner
             .iter()
@@ -58,10 +65,12 @@ impl<T> Grid<T> {
         })
     }
 
+    #[allow(dead_code)]
     pub fn width(&self) -> usize {
         self.width
     }
 
+    #[allow(dead_code)]
     pub fn height(&self) -> usize {
         self.height
     }
@@ -160,6 +169,7 @@ impl Sub for &Tile {
 //     }
 // }
 //
+#[allow(dead_code)]
 fn diff_iter<'a, T: 'a>(a: impl Iterator<Item = &'a T>, b: impl Iterator<Item = &'a T>) -> usize
 where
     for<'b> &'b T: Sub<Output = usize>,
@@ -208,13 +218,15 @@ impl Grid<Tile> {
 }
 
 fn part_one(s: &str) -> usize {
-    s.split("\n\n")
+    normalize(s)
+        .split("\n\n")
         .map(|s| s.parse::<Grid<Tile>>().unwrap().sum(0))
         .sum()
 }
 
 fn part_two(s: &str) -> usize {
-    s.split("\n\n")
+    normalize(s)
+        .split("\n\n")
         .map(|s| s.parse::<Grid<Tile>>().unwrap().sum(1))
         .sum()
 }
@@ -262,5 +274,13 @@ mod tests {
     fn example_pt2() {
         assert_eq!(part_two(include_str!("../example")), 400)
     }
+
+    #[test]
+    fn input_variants() {
+        for input in common::input::variants(include_str!("../example")) {
+            assert_eq!(part_one(&input), 405);
+            assert_eq!(part_two(&input), 400);
+        }
+    }
 }
 
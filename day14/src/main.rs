@@ -1,3 +1,4 @@
+use common::{cli::Args, input::normalize};
 use memoize::memoize;
 use std::{
     collections::HashMap,
@@ -6,8 +7,14 @@ use std::{
 };
 
 fn main() {
-    println!("{}", part_one(include_str!("../input")));
-    println!("{}", part_two(include_str!("../input"), 1000000000));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(14, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!(
+        "{}",
+        cache.get_or_solve(2, &input, |s| part_two(s, 1000000000))
+    );
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -84,12 +91,14 @@ impl<T> Grid<T> {
         self.inner.get(self.dim.index_from_coord(coord)).unwrap()
     }
 
+    #[allow(dead_code)]
     pub fn get_mut(&mut self, coord: &Coord) -> &mut T {
         self.inner
             .get_mut(self.dim.index_from_coord(coord))
             .unwrap()
     }
 
+    #[allow(dead_code)]
     pub fn iter_coord(&self) -> impl Iterator<Item = (Coord, &'_ T)> {
         self.inner
             .iter()
@@ -266,13 +275,13 @@ impl Grid<Tile> {
 }
 
 fn part_one(s: &str) -> usize {
-    let mut grid: Grid<Tile> = s.parse().unwrap();
+    let mut grid: Grid<Tile> = normalize(s).parse().unwrap();
     grid.shift(Direction::North);
     grid.measure_load()
 }
 
 fn part_two(s: &str, cycles: usize) -> usize {
-    let mut grid: Grid<Tile> = s.parse().unwrap();
+    let mut grid: Grid<Tile> = normalize(s).parse().unwrap();
     let mut cache: HashMap<Grid<Tile>, usize> = HashMap::new();
     let mut found_cycle = false;
     (0..cycles)
@@ -311,6 +320,13 @@ mod tests {
         assert_eq!(part_one(include_str!("../example")), 136)
     }
 
+    #[test]
+    fn input_variants() {
+        for input in common::input::variants(include_str!("../example")) {
+            assert_eq!(part_one(&input), 136)
+        }
+    }
+
     #[test]
     fn part_one_answer() {
         assert_eq!(part_one(include_str!("../input")), 110821)
@@ -1,12 +1,16 @@
 use std::{
     collections::HashMap,
-    hash::{BuildHasher, Hash, Hasher},
-    str::FromStr,
+    hash::{Hash, Hasher},
 };
 
+use common::{cli::Args, input::normalize};
+
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(15, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -29,9 +33,11 @@ impl Hasher for MyHasher {
     }
 
     fn write(&mut self, bytes: &[u8]) {
+        // `str::hash` appends a 0xff terminator which is not part of the
+        // HASH algorithm, newlines are stripped by `steps`
         bytes
             .iter()
-            .filter(|u| !u.is_ascii_whitespace() && u.is_ascii())
+            .filter(|u| u.is_ascii())
             .for_each(|u| self.write_char(*u))
     }
 }
@@ -39,12 +45,17 @@ impl Hasher for MyHasher {
 fn hash_str(s: &str) -> u64 {
     let mut h = MyHasher::default();
     s.hash(&mut h);
-    let out = h.finish();
-    out
+    h.finish()
+}
+
+/// The puzzle says to ignore newlines, so a sequence wrapped over several
+/// lines is joined back before splitting into steps.
+fn steps(input: &str) -> String {
+    normalize(input).replace('\n', "")
 }
 
 fn part_one(input: &str) -> u64 {
-    input.split(',').map(hash_str).sum()
+    steps(input).split(',').map(hash_str).sum()
 }
 
 #[derive(Debug)]
@@ -121,6 +132,7 @@ impl<'a> BoxesMap<'a> {
 }
 
 fn part_two(input: &str) -> u64 {
+    let input = steps(input);
     let mut bm = BoxesMap::new();
     input.split(',').for_each(|s| bm.operation(s));
     bm.sum()
@@ -153,5 +165,15 @@ mod tests {
     fn example_part_2() {
         assert_eq!(part_two(include_str!("../example")), 145)
     }
-}
 
+    #[test]
+    fn input_variants() {
+        for input in common::input::variants(include_str!("../example")) {
+            assert_eq!(part_one(&input), 1320);
+            assert_eq!(part_two(&input), 145);
+        }
+        let wrapped = include_str!("../example").replacen("pc=4,", "pc=4,\n", 1);
+        assert_eq!(part_one(&wrapped), 1320);
+        assert_eq!(part_two(&wrapped), 145);
+    }
+}
@@ -1,139 +1,253 @@
-use std::str::FromStr;
+use std::{cmp::Ordering, collections::BTreeMap, ops::Range, str::FromStr};
 
+use common::{
+    cli::{Args, EXPLAIN},
+    explain::Trace,
+    input::normalize,
+};
 use nom::{
-    branch::alt,
     bytes::complete::tag,
-    character::complete::u32,
-    combinator::{map, opt},
-    multi::many1,
-    sequence::delimited,
+    character::complete::{alpha1, space1, u32},
+    combinator::{all_consuming, map},
+    multi::separated_list1,
+    sequence::{delimited, separated_pair},
     IResult,
 };
 
+/// `--bag=` replaces the puzzle's bag, in the same syntax as a draw.
+/// `--query=tightest`, `--query=conflicts` and `--query=likely:SIZE` print the
+/// smallest bag for every game, the draws the bag rules out, or the likeliest
+/// bag of `SIZE` cubes instead of the answers.
 fn main() {
-    dbg!(part_one(INPUT.lines()));
-    dbg!(part_two(INPUT.lines()));
+    let (args, flags) = Args::parse_with(&[EXPLAIN, "--bag", "--query"]);
+    let mut bag = None;
+    let mut query = None;
+    for flag in &flags {
+        match flag.split_once('=') {
+            Some(("--bag", set)) => match set.parse::<Set>() {
+                Ok(set) => bag = Some(set),
+                Err(_) => {
+                    eprintln!("bad bag {set}, expected e.g. `12 red, 13 green`");
+                    std::process::exit(2)
+                }
+            },
+            Some(("--query", name)) => query = Some(name),
+            _ => {
+                eprintln!("unknown flag {flag}");
+                std::process::exit(2)
+            }
+        }
+    }
+    let input = args.read_input(INPUT);
+    for diagnostic in diagnose(&input) {
+        eprintln!("warning: {diagnostic}");
+    }
+    if let Some(query) = query {
+        let games = parse_games(&input);
+        let bag = bag.unwrap_or_else(|| BAG.parse().unwrap());
+        match query.split_once(':') {
+            None if query == "tightest" => println!("{}", tightest_bag(&games)),
+            None if query == "conflicts" => {
+                for conflict in conflicts(&games, &bag) {
+                    println!("{conflict}");
+                }
+            }
+            Some(("likely", size)) if size.parse::<u32>().is_ok() => {
+                match likely_bag(&games, size.parse().unwrap()) {
+                    Some(bag) => println!("{bag}"),
+                    None => {
+                        eprintln!("no bag of {size} cubes could have played every game");
+                        std::process::exit(1)
+                    }
+                }
+            }
+            _ => {
+                eprintln!("unknown query {query}, expected tightest, conflicts or likely:SIZE");
+                std::process::exit(2)
+            }
+        }
+        return;
+    }
+    let cache = args.cache(2, include_str!("main.rs"));
+    if let Some(format) = args.explain {
+        let bag = bag.clone().unwrap_or_else(|| BAG.parse().unwrap());
+        println!("{}", explain(&input, &bag).render(format));
+    }
+    let answer = match &bag {
+        Some(bag) => cache
+            .clone()
+            .keyed(&bag.to_string())
+            .get_or_solve(1, &input, |input| possible_games(input, bag)),
+        None => cache.get_or_solve(1, &input, part_one),
+    };
+    args.answer(answer);
+    args.answer(cache.get_or_solve(2, &input, part_two));
 }
 
 const INPUT: &str = include_str!("../input");
 
-#[derive(Debug)]
+const BAG: &str = "12 red, 13 green, 14 blue";
+
+/// Cube counts by colour. A colour that isn't listed counts as zero.
+#[derive(Debug, Clone, Default)]
 struct Set {
-    red: u32,
-    blue: u32,
-    green: u32,
+    counts: BTreeMap<String, u32>,
 }
 
 impl Set {
+    fn get(&self, colour: &str) -> u32 {
+        self.counts.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Product of the counts of the colours listed, so a listed zero makes
+    /// the power zero.
     fn power(&self) -> u32 {
-        self.red * self.blue * self.green
+        self.counts.values().product()
     }
-}
 
-impl PartialEq for Set {
-    fn eq(&self, other: &Self) -> bool {
-        self.red == other.red && self.green == other.green && self.blue == other.blue
+    /// Product of the counts of every colour in `colours`, whether or not
+    /// this set lists it. Equal sets have equal power over any colours.
+    #[allow(dead_code)]
+    fn power_over(&self, colours: &Set) -> u32 {
+        colours
+            .counts
+            .keys()
+            .map(|colour| self.get(colour))
+            .product()
     }
-}
 
-impl PartialOrd for Set {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        let r = self.red.partial_cmp(&other.red);
-        let g = self.green.partial_cmp(&other.green);
-        let b = self.blue.partial_cmp(&other.blue);
+    fn colours(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts
+            .iter()
+            .map(|(colour, count)| (colour.as_str(), *count))
+    }
 
-        match (r, g, b) {
-            (
-                Some(std::cmp::Ordering::Less) | Some(std::cmp::Ordering::Equal),
-                Some(std::cmp::Ordering::Less) | Some(std::cmp::Ordering::Equal),
-                Some(std::cmp::Ordering::Less) | Some(std::cmp::Ordering::Equal),
-            ) => Some(std::cmp::Ordering::Less),
-            (
-                Some(std::cmp::Ordering::Greater),
-                Some(std::cmp::Ordering::Greater),
-                Some(std::cmp::Ordering::Greater),
-            ) => Some(std::cmp::Ordering::Greater),
-            _ => None,
-        }
+    fn combine(&self, other: &Set, f: impl Fn(u32, u32) -> u32) -> Set {
+        self.union_colours(other)
+            .map(|colour| (colour, f(self.get(colour), other.get(colour))))
+            .collect()
     }
-}
 
+    /// The most of each colour in either set.
+    fn join(&self, other: &Set) -> Set {
+        self.combine(other, u32::max)
+    }
 
-fn blue(s: &str) -> IResult<&str, u32> {
-    let (next, out) = u32(s)?;
-    let (next, _) = tag(" blue")(next)?;
-    Ok((next, out))
-}
+    /// The least of each colour in either set.
+    #[allow(dead_code)]
+    fn meet(&self, other: &Set) -> Set {
+        self.combine(other, u32::min)
+    }
 
-fn red(s: &str) -> IResult<&str, u32> {
-    let (next, out) = u32(s)?;
-    let (next, _) = tag(" red")(next)?;
-    Ok((next, out))
-}
+    #[allow(dead_code)]
+    fn sum(&self, other: &Set) -> Set {
+        self.combine(other, u32::saturating_add)
+    }
+
+    /// Whether `other` could be drawn from this set.
+    fn contains(&self, other: &Set) -> bool {
+        other <= self
+    }
 
-fn green(s: &str) -> IResult<&str, u32> {
-    let (next, out) = u32(s)?;
-    let (next, _) = tag(" green")(next)?;
-    Ok((next, out))
+    // every colour named by either set
+    fn union_colours<'a>(&'a self, other: &'a Set) -> impl Iterator<Item = &'a str> {
+        let mut colours = self
+            .counts
+            .keys()
+            .chain(other.counts.keys())
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        colours.sort_unstable();
+        colours.dedup();
+        colours.into_iter()
+    }
 }
 
-#[derive(Debug)]
-enum Color {
-    Red(u32),
-    Green(u32),
-    Blue(u32),
+impl<S: Into<String>> FromIterator<(S, u32)> for Set {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(iter: T) -> Self {
+        Set {
+            counts: iter
+                .into_iter()
+                .map(|(colour, count)| (colour.into(), count))
+                .collect(),
+        }
+    }
 }
 
-fn rgb(s: &str) -> IResult<&str, Color> {
-    let (next, _) = opt(tag(", "))(s)?;
-    let (next, out) = alt((
-        map(red, Color::Red),
-        map(green, Color::Green),
-        map(blue, Color::Blue),
-    ))(next)?;
-    // dbg!(&next, &out);
-    Ok((next, out))
+/// Colours in alphabetical order, in the syntax `FromStr` reads.
+impl std::fmt::Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubes = self
+            .colours()
+            .map(|(colour, count)| format!("{count} {colour}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", cubes.join(", "))
+    }
 }
 
-fn set(s: &str) -> IResult<&str, Option<Set>> {
-    dbg!(&s);
-    let mut red = 0u32;
-    let mut green = 0u32;
-    let mut blue = 0u32;
+/// The same `3 blue, 4 red` syntax as a draw, so a bag can be given as any
+/// list of colours and counts.
+impl FromStr for Set {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match all_consuming(set)(s.trim()) {
+            Ok((_, set)) => Ok(set),
+            Err(_) => Err(()),
+        }
+    }
+}
 
-    let res = many1(rgb)(s);
-    let (next, colors) = res?;
+impl PartialEq for Set {
+    fn eq(&self, other: &Self) -> bool {
+        self.union_colours(other)
+            .all(|colour| self.get(colour) == other.get(colour))
+    }
+}
 
-    for color in colors {
-        match color {
-            Color::Red(r) => red = r,
-            Color::Green(g) => green = g,
-            Color::Blue(b) => blue = b,
+/// Component-wise: one set is below another when it has no more of any
+/// colour, and sets with more of one colour but less of another are
+/// incomparable.
+impl PartialOrd for Set {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+        for colour in self.union_colours(other) {
+            match (ordering, self.get(colour).cmp(&other.get(colour))) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, o) => ordering = o,
+                (a, o) if a == o => {}
+                _ => return None,
+            }
         }
+        Some(ordering)
     }
+}
+
+fn cubes(s: &str) -> IResult<&str, (&str, u32)> {
+    map(separated_pair(u32, space1, alpha1), |(count, colour)| {
+        (colour, count)
+    })(s)
+}
 
-    Ok((next, Some(Set { red, green, blue })))
+fn set(s: &str) -> IResult<&str, Set> {
+    map(separated_list1(tag(", "), cubes), Set::from_iter)(s)
 }
 
 #[derive(Debug)]
 struct Game {
+    id: u32,
     sets: Vec<Set>,
 }
 
 impl Game {
     fn is_valid(&self, max_set: &Set) -> bool {
-        self.sets.iter().all(|f| f <= max_set)
+        self.sets.iter().all(|f| max_set.contains(f))
     }
 
     fn minimum_set(&self) -> Set {
-        let min_red = self.sets.iter().map(|f| f.red).max().unwrap();
-        let min_green = self.sets.iter().map(|f| f.green).max().unwrap();
-        let min_blue = self.sets.iter().map(|f| f.blue).max().unwrap();
-        Set {
-            red: min_red,
-            green: min_green,
-            blue: min_blue,
-        }
+        self.sets
+            .iter()
+            .fold(Set::default(), |min, set| min.join(set))
     }
 }
 
@@ -141,7 +255,7 @@ impl FromStr for Game {
     type Err = ();
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match game_from_s(value) {
+        match all_consuming(game_from_s)(value) {
             Ok((_, game)) => Ok(game),
             Err(_) => Err(()),
         }
@@ -149,93 +263,464 @@ impl FromStr for Game {
 }
 
 fn game_from_s(s: &str) -> IResult<&str, Game> {
-    let (next, _) = delimited(tag("Game "), u32, tag(": "))(s)?;
-    let sets = next
-        .split("; ")
-        .filter_map(|s| {
-            let (_, set) = set(s).unwrap();
-            set
-        })
-        .collect::<Vec<_>>();
-    Ok(("", Game { sets }))
+    let (next, id) = delimited(tag("Game "), u32, tag(": "))(s)?;
+    let (next, sets) = separated_list1(tag("; "), set)(next)?;
+    Ok((next, Game { id, sets }))
+}
+
+fn parse_games(input: &str) -> Vec<Game> {
+    normalize(input)
+        .lines()
+        .filter_map(|line| Game::from_str(line).ok())
+        .collect()
 }
 
-// fn game_is_valid(this_game: &Set, max_game: &Set) -> bool {
-//     this_game <= max_game
-// }
+#[derive(Debug, PartialEq, Eq)]
+enum Diagnostic {
+    /// The line isn't blank but doesn't parse as a game, so it's left out.
+    Unparsed { line: usize, text: String },
+    /// The ID is used by more than one game.
+    Duplicate(u32),
+    /// No game has these IDs, though a later one does.
+    Missing(Range<u32>),
+}
 
-fn part_one(input: impl Iterator<Item = &'static str>) -> u32 {
-    let max_set = Set {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
-    input
-        .enumerate()
-        .filter_map(
-            |(i, line)| match Game::from_str(line).ok()?.is_valid(&max_set) {
-                true => Some(i as u32 + 1),
-                false => None,
-            },
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::Unparsed { line, text } => write!(f, "line {line} isn't a game: {text}"),
+            Diagnostic::Duplicate(id) => write!(f, "more than one game has ID {id}"),
+            Diagnostic::Missing(ids) if ids.len() == 1 => write!(f, "no game has ID {}", ids.start),
+            Diagnostic::Missing(ids) => {
+                write!(f, "no game has IDs {} to {}", ids.start, ids.end - 1)
+            }
+        }
+    }
+}
+
+/// Lines that aren't games, and problems with the game IDs, which should run
+/// from 1 without gaps or repeats. Gaps are reported as one range each.
+fn diagnose(input: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut seen = BTreeMap::new();
+    for (i, line) in normalize(input).lines().enumerate() {
+        match Game::from_str(line) {
+            Ok(game) => *seen.entry(game.id).or_insert(0) += 1,
+            Err(_) if line.trim().is_empty() => {}
+            Err(_) => diagnostics.push(Diagnostic::Unparsed {
+                line: i + 1,
+                text: line.to_string(),
+            }),
+        }
+    }
+    let mut next = 1;
+    for (&id, &count) in &seen {
+        if id > next {
+            diagnostics.push(Diagnostic::Missing(next..id));
+        }
+        if count > 1 {
+            diagnostics.push(Diagnostic::Duplicate(id));
+        }
+        next = next.max(id.saturating_add(1));
+    }
+    diagnostics
+}
+
+/// The smallest bag every game could have been played with.
+fn tightest_bag(games: &[Game]) -> Set {
+    games
+        .iter()
+        .fold(Set::default(), |bag, game| bag.join(&game.minimum_set()))
+}
+
+/// A draw that couldn't have come from a bag.
+#[derive(Debug, PartialEq)]
+struct Conflict {
+    game: u32,
+    // index into the game's sets
+    draw: usize,
+    // how many more of each colour were drawn than the bag holds
+    excess: Set,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Game {} set {}: {} too many",
+            self.game,
+            self.draw + 1,
+            self.excess
         )
-        .sum()
+    }
 }
 
-fn part_two(input: impl Iterator<Item = &'static str>) -> u32 {
-    input
-        .filter_map(|line| Game::from_str(line).ok())
-        .map(|game| {
-            let min = game.minimum_set();
-            dbg!(&game, &min);
-            min.power()
+/// Every draw that rules out `bag`, in game order.
+fn conflicts(games: &[Game], bag: &Set) -> Vec<Conflict> {
+    games
+        .iter()
+        .flat_map(|game| {
+            game.sets.iter().enumerate().filter_map(|(draw, set)| {
+                let excess = set
+                    .colours()
+                    .filter(|(colour, count)| *count > bag.get(colour))
+                    .map(|(colour, count)| (colour, count - bag.get(colour)))
+                    .collect::<Set>();
+                match excess.counts.is_empty() {
+                    true => None,
+                    false => Some(Conflict {
+                        game: game.id,
+                        draw,
+                        excess,
+                    }),
+                }
+            })
         })
+        .collect()
+}
+
+/// The bag of `size` cubes most likely to produce the draws, if each cube is
+/// put back before the next is drawn. Every cube drawn is then independent,
+/// so the likelihood is the product over colours of `(n / size) ^ drawn`.
+/// Starting from the tightest bag, so no draw is ruled out, cubes are handed
+/// out one at a time to whichever colour gains the most. `None` if `size` is
+/// smaller than the tightest bag.
+fn likely_bag(games: &[Game], size: u32) -> Option<Set> {
+    let drawn = games
+        .iter()
+        .flat_map(|game| &game.sets)
+        .fold(Set::default(), |total, set| total.sum(set));
+    let mut bag = tightest_bag(games);
+    let mut cubes = bag
+        .colours()
+        .try_fold(0u32, |total, (_, count)| total.checked_add(count))?;
+    if cubes > size {
+        return None;
+    }
+    while cubes < size {
+        let gain = |(colour, n): (&String, &u32)| {
+            drawn.get(colour) as f64 * ((*n + 1) as f64 / *n as f64).ln()
+        };
+        let (colour, _) = bag
+            .counts
+            .iter()
+            .max_by(|a, b| gain(*a).total_cmp(&gain(*b)).then(b.0.cmp(a.0)))?;
+        let colour = colour.clone();
+        *bag.counts.get_mut(&colour).unwrap() += 1;
+        cubes += 1;
+    }
+    Some(bag)
+}
+
+fn part_one(input: &str) -> u32 {
+    possible_games(input, &BAG.parse().unwrap())
+}
+
+/// Sum of the IDs of the games that could have been played with `bag`.
+fn possible_games(input: &str, bag: &Set) -> u32 {
+    parse_games(input)
+        .iter()
+        .filter(|game| game.is_valid(bag))
+        .map(|game| game.id)
         .sum()
 }
 
+fn part_two(input: &str) -> u32 {
+    parse_games(input)
+        .iter()
+        .map(|game| game.minimum_set().power())
+        .sum()
+}
+
+fn explain(input: &str, bag: &Set) -> Trace {
+    let mut trace = Trace::new();
+    let games = parse_games(input)
+        .into_iter()
+        .map(|game| (format!("Game {}", game.id), game))
+        .collect::<Vec<_>>();
+
+    for (item, game) in &games {
+        let conflicts = conflicts(std::slice::from_ref(game), bag);
+        let verdict = match conflicts.is_empty() {
+            true => "possible",
+            false => "impossible",
+        };
+        let step = trace.step(1, item, verdict);
+        for conflict in conflicts {
+            let over = conflict
+                .excess
+                .colours()
+                .map(|(colour, excess)| {
+                    let max = bag.get(colour);
+                    format!("{} {colour} > {max}", max + excess)
+                })
+                .collect::<Vec<_>>();
+            step.detail(format!("set {}", conflict.draw + 1), over.join(", "));
+        }
+    }
+
+    for (item, game) in &games {
+        let min = game.minimum_set();
+        let step = trace.step(2, item, min.power());
+        for (colour, count) in min.colours() {
+            step.detail(colour, count);
+        }
+    }
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    fn set(s: &str) -> Set {
+        s.parse().unwrap()
+    }
+
     #[test]
     fn test_part_one() {
-        assert!(
-            Set {
-                red: 0,
-                blue: 2,
-                green: 13
-            } <= Set {
-                red: 12,
-                blue: 14,
-                green: 13
-            }
+        assert!(set("0 red, 2 blue, 13 green") <= set(BAG));
+        assert!(set("2 blue, 13 green") <= set(BAG));
+        assert_eq!(set("1 purple").partial_cmp(&set(BAG)), None);
+        assert_eq!(set(BAG).partial_cmp(&set(BAG)), Some(Ordering::Equal));
+        assert_eq!(
+            set("1 red").partial_cmp(&set("1 red, 0 blue")),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            set("1 red").partial_cmp(&set("1 red, 1 blue")),
+            Some(Ordering::Less)
         );
     }
 
     #[test]
     fn test_power() {
+        assert_eq!(set("0 red, 2 blue, 13 green").power(), 0);
+        assert_eq!(set("4 red, 2 blue, 6 green").power(), 48);
+    }
+
+    #[test]
+    fn test_power_over() {
+        let bag = set(BAG);
+        assert_eq!(set("2 blue, 13 green").power_over(&bag), 0);
+        assert_eq!(set("1 red, 0 blue").power_over(&bag), 0);
+        assert_eq!(set("4 red, 2 blue, 6 green").power_over(&bag), 48);
+        assert_eq!(set("4 red, 2 blue").power_over(&set("1 red, 1 blue")), 8);
         assert_eq!(
-            Set {
-                red: 0,
-                blue: 2,
-                green: 13
+            set("1 red, 0 blue").power_over(&set("1 red")),
+            set("1 red").power_over(&set("1 red"))
+        );
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = set("3 red, 1 blue");
+        let b = set("1 red, 4 green");
+        assert_eq!(a.join(&b), set("3 red, 1 blue, 4 green"));
+        assert_eq!(a.meet(&b), set("1 red"));
+        assert_eq!(a.sum(&b), set("4 red, 1 blue, 4 green"));
+        assert!(a.join(&b).contains(&a));
+        assert!(!a.contains(&b));
+        assert!(a.contains(&Set::default()));
+    }
+
+    fn any_set() -> impl Strategy<Value = Set> {
+        let colour = prop::sample::select(vec!["red", "green", "blue", "cyan"]);
+        prop::collection::vec((colour, 0u32..8), 0..5).prop_map(Set::from_iter)
+    }
+
+    proptest! {
+        #[test]
+        fn lattice_laws(a in any_set(), b in any_set(), c in any_set()) {
+            prop_assert_eq!(a.join(&b), b.join(&a));
+            prop_assert_eq!(a.meet(&b), b.meet(&a));
+            prop_assert_eq!(a.join(&b).join(&c), a.join(&b.join(&c)));
+            prop_assert_eq!(a.meet(&b).meet(&c), a.meet(&b.meet(&c)));
+            prop_assert_eq!(a.join(&a), a.clone());
+            prop_assert_eq!(a.meet(&a), a.clone());
+            prop_assert_eq!(a.join(&a.meet(&b)), a.clone());
+            prop_assert_eq!(a.meet(&a.join(&b)), a.clone());
+            prop_assert!(a <= a.join(&b) && a.meet(&b) <= a);
+            prop_assert!(a.join(&b) <= a.sum(&b));
+        }
+
+        #[test]
+        fn order_laws(a in any_set(), b in any_set(), c in any_set()) {
+            prop_assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal));
+            prop_assert_eq!(a <= b && b <= a, a == b);
+            prop_assert_eq!(a.partial_cmp(&b).map(Ordering::reverse), b.partial_cmp(&a));
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
             }
-            .power(),
-            0
+            prop_assert_eq!(a <= b, a.join(&b) == b);
+            prop_assert_eq!(a <= b, a.meet(&b) == a);
+            prop_assert_eq!(b.contains(&a), a <= b);
+            if a == b {
+                prop_assert_eq!(a.power_over(&c), b.power_over(&c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_game_ids() {
+        let input = "Game 3: 1 red; 2 blue
+Game 7: 20 red
+Game 5: 1 green
+Game 3: 2 green";
+        assert_eq!(part_one(input), 3 + 5 + 3);
+        assert_eq!(
+            diagnose(input),
+            [
+                Diagnostic::Missing(1..3),
+                Diagnostic::Duplicate(3),
+                Diagnostic::Missing(4..5),
+                Diagnostic::Missing(6..7),
+            ]
         );
         assert_eq!(
-            Set {
-                red: 4,
-                blue: 2,
-                green: 6
-            }
-            .power(),
-            48
+            Diagnostic::Duplicate(3).to_string(),
+            "more than one game has ID 3"
+        );
+        assert_eq!(Diagnostic::Missing(4..5).to_string(), "no game has ID 4");
+        assert_eq!(
+            Diagnostic::Missing(1..3).to_string(),
+            "no game has IDs 1 to 2"
         );
+        assert_eq!(diagnose(include_str!("../test")), []);
+        assert_eq!(diagnose(INPUT), []);
+    }
+
+    #[test]
+    fn test_large_and_unparsed_ids() {
+        let input = "Game 4000000000: 1 red
+Game 1: 2 blue
+
+Game two: 3 green
+Game 2: 1 red; 1 magenta,";
+        assert_eq!(
+            diagnose(input),
+            [
+                Diagnostic::Unparsed {
+                    line: 4,
+                    text: "Game two: 3 green".to_string()
+                },
+                Diagnostic::Unparsed {
+                    line: 5,
+                    text: "Game 2: 1 red; 1 magenta,".to_string()
+                },
+                Diagnostic::Missing(2..4000000000),
+            ]
+        );
+        assert_eq!(
+            diagnose("Game 0: 1 red\nGame 0: 1 red"),
+            [Diagnostic::Duplicate(0)]
+        );
+    }
+
+    #[test]
+    fn test_bag_inference() {
+        let games = parse_games(include_str!("../test"));
+        assert_eq!(tightest_bag(&games), set("20 red, 13 green, 15 blue"));
+        assert_eq!(conflicts(&games, &tightest_bag(&games)), []);
+        assert_eq!(
+            conflicts(&games, &set(BAG)),
+            [
+                Conflict {
+                    game: 3,
+                    draw: 0,
+                    excess: set("8 red")
+                },
+                Conflict {
+                    game: 4,
+                    draw: 2,
+                    excess: set("2 red, 1 blue")
+                },
+            ]
+        );
+        assert_eq!(
+            likely_bag(&games, 159),
+            Some(set("61 red, 48 green, 50 blue"))
+        );
+        assert_eq!(likely_bag(&games, 48), Some(tightest_bag(&games)));
+        assert_eq!(likely_bag(&games, 47), None);
+        for size in 48..100 {
+            assert_eq!(conflicts(&games, &likely_bag(&games, size).unwrap()), []);
+        }
+
+        let games = parse_games("Game 1: 3 red, 1 blue; 0 green");
+        assert_eq!(likely_bag(&games, 4), Some(set("3 red, 1 blue")));
+        assert_eq!(likely_bag(&games, 8), Some(set("6 red, 2 blue")));
+        assert_eq!(likely_bag(&games, 3), None);
+        assert_eq!(likely_bag(&games, 1), None);
+        assert_eq!(
+            set("4 red").sum(&set("4294967295 red")),
+            set("4294967295 red")
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            set("3 red, 1 blue, 0 green").to_string(),
+            "1 blue, 0 green, 3 red"
+        );
+        let s = set("12 red, 13 green, 14 blue");
+        assert_eq!(s.to_string().parse::<Set>().unwrap(), s);
+        let games = parse_games(include_str!("../test"));
+        let lines = conflicts(&games, &set(BAG))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "Game 3 set 1: 8 red too many",
+                "Game 4 set 3: 1 blue, 2 red too many"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_five_colours() {
+        let input = "Game 1: 3 blue, 4 red; 1 cyan, 2 green, 6 blue; 7 magenta
+Game 2: 1 blue, 2 green; 3 green, 20 cyan, 1 red; 1 magenta
+Game 3: 2 red, 1 purple";
+        let bag = set("5 red, 5 green, 10 blue, 5 cyan, 10 magenta");
+        assert_eq!(possible_games(input, &bag), 1);
+        assert_eq!(part_two(input), 336 + 60 + 2);
+        assert_eq!(set("1 red, 0 blue"), set("1 red"));
+        assert!("3 blue,".parse::<Set>().is_err());
+        assert!("blue 3".parse::<Set>().is_err());
     }
 
     #[test]
     fn test_part_two() {
-        let out = part_two(include_str!("../test").lines());
+        let out = part_two(include_str!("../test"));
         assert_eq!(out, 2286);
     }
-}
 
+    #[test]
+    fn test_explain() {
+        let trace = explain(include_str!("../test"), &set(BAG));
+        let rejected = trace
+            .steps()
+            .filter(|step| step.verdict() == "impossible")
+            .map(|step| step.item())
+            .collect::<Vec<_>>();
+        assert_eq!(rejected, ["Game 3", "Game 4"]);
+        assert!(trace
+            .to_text()
+            .contains("[part 1] Game 3: impossible\n    set 1: 20 red > 12\n"));
+        assert!(trace.to_json().contains(
+            r#"{"part":2,"item":"Game 1","verdict":"48","details":{"blue":"6","green":"2","red":"4"}}"#
+        ));
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 8);
+            assert_eq!(part_two(&input), 2286);
+        }
+    }
+}
@@ -1,53 +1,75 @@
-use nom::bytes::complete::take_till;
-use nom::character::complete::digit1;
-use nom::combinator::map_res;
-use nom::multi::many1;
+use common::{
+    cli::{Args, EXPLAIN},
+    explain::Trace,
+    input::normalize,
+};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, hex_digit1, one_of};
+use nom::combinator::{map_res, opt};
+use nom::sequence::preceded;
 use nom::IResult;
-use std::{collections::HashSet, convert::Infallible, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
 
 const INPUT: &str = include_str!("../input");
 
+/// `--signed`, `--hex=0x` and `--wrap` read numbers in another syntax, see
+/// `NumberSyntax`.
 fn main() {
-    dbg!(part_one(INPUT));
-    dbg!(part_two(INPUT));
+    let (args, flags) = Args::parse_with(&[EXPLAIN, "--signed", "--hex", "--wrap"]);
+    let syntax = match flags.is_empty() {
+        true => None,
+        false => Some(NumberSyntax::from_flags(&flags).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(2)
+        })),
+    };
+    let input = args.read_input(INPUT);
+    let cache = args.cache(3, include_str!("main.rs"));
+    if let Some(format) = args.explain {
+        let trace = explain(&input, syntax.unwrap_or_default());
+        println!("{}", trace.render(format));
+    }
+    let answers = match syntax {
+        None => [
+            cache.get_or_solve(1, &input, part_one),
+            cache.get_or_solve(2, &input, part_two),
+        ],
+        Some(syntax) => {
+            let cache = cache.keyed(&format!("{syntax:?}"));
+            [
+                cache.get_or_solve(1, &input, |input| sum_part_numbers(input, syntax)),
+                cache.get_or_solve(2, &input, |input| sum_gear_ratios(input, syntax)),
+            ]
+        }
+    };
+    for answer in answers {
+        args.answer(answer);
+    }
 }
 
 #[derive(Debug)]
 struct Grid {
-    inner: String,
+    // one byte per cell, row by row
+    inner: Vec<u8>,
     width: usize,
-    height: usize,
+    syntax: NumberSyntax,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+// y first so coordinates sort in reading order
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Coord {
-    x: usize,
     y: usize,
-}
-
-impl Coord {
-    fn adjacent(&self) -> impl Iterator<Item = Coord> + '_ {
-        (self.x.saturating_sub(1)..=self.x + 1)
-            .flat_map(|x| (self.y.saturating_sub(1)..=self.y + 1).map(move |y| Coord { x, y }))
-            .filter(move |c| c != self)
-    }
+    x: usize,
 }
 
 impl Grid {
-    fn get(&self, Coord { x, y }: &Coord) -> Option<char> {
-        if x >= &self.width || y >= &self.height {
-            return None;
-        }
-        let index = y * self.width + x;
-        let out = self.inner.chars().nth(index);
-        out
-    }
-
-    fn has_adjacent_symbol(&self, coord: &Coord) -> bool {
-        coord
-            .adjacent()
-            .filter_map(|c| self.get(&c))
-            .any(|char| !matches!(char, '.' | '0'..='9'))
+    /// Reads numbers written in `syntax` rather than plain digits.
+    fn with_syntax(self, syntax: NumberSyntax) -> Self {
+        Grid { syntax, ..self }
     }
 
     fn index_to_coord(&self, i: &usize) -> Coord {
@@ -57,27 +79,33 @@ impl Grid {
     }
 
     fn iter_lines(&self) -> impl Iterator<Item = &str> {
-        (0..self.height).map(|i| {
-            let start = i * self.height;
-            let end = start + self.width;
-            &self.inner[start..end]
-        })
+        self.inner
+            .chunks(self.width)
+            .map(|line| std::str::from_utf8(line).unwrap())
     }
 
     fn iter_ranges(&self) -> impl Iterator<Item = NumberRange> + '_ {
-        self.iter_lines()
-            .enumerate()
-            .filter_map(|(y, line)| {
-                let (_, line) = digit_indexes(line).ok()?;
-                Some(
-                    line.into_iter()
-                        .map(move |NumberLine { origin, num }| NumberRange {
-                            coord: Coord { x: origin, y },
-                            num,
-                        }),
-                )
-            })
-            .flatten()
+        // each line and the index of its first cell; with wrapping the whole
+        // grid is read as one line in reading order
+        let lines = match self.syntax.wrap {
+            true => vec![(0, std::str::from_utf8(&self.inner).unwrap())],
+            false => self
+                .iter_lines()
+                .enumerate()
+                .map(|(y, line)| (y * self.width, line))
+                .collect(),
+        };
+        lines.into_iter().flat_map(move |(start, line)| {
+            self.syntax
+                .numbers(line)
+                .into_iter()
+                .map(move |NumberLine { origin, len, num }| NumberRange {
+                    coord: self.index_to_coord(&(start + origin)),
+                    len,
+                    num,
+                    width: self.width,
+                })
+        })
     }
 }
 
@@ -89,7 +117,7 @@ struct GridCharIter<'a> {
 impl Iterator for GridCharIter<'_> {
     type Item = char;
     fn next(&mut self) -> Option<Self::Item> {
-        let out = self.grid.inner.chars().nth(self.cur);
+        let out = self.grid.inner.get(self.cur).map(|&b| b as char);
         self.cur += 1;
         out
     }
@@ -105,135 +133,338 @@ impl<'a> IntoIterator for &'a Grid {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum GridError {
+    Empty,
+    // `row` has `len` cells where the first row has `width`
+    Ragged {
+        row: usize,
+        len: usize,
+        width: usize,
+    },
+    // cells are bytes, so `c` at `row` and `col` (in chars) can't be one
+    NonAscii {
+        row: usize,
+        col: usize,
+        c: char,
+    },
+}
+
 impl FromStr for Grid {
-    type Err = Infallible;
+    type Err = GridError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines = s.lines().collect::<Vec<_>>();
-        let height = lines.len();
-        let width = lines[0].len();
-        let mut inner = s.to_owned();
-        inner.retain(|c| !c.is_whitespace());
+        let lines = s.lines().map(str::trim_end).collect::<Vec<_>>();
+        for (row, line) in lines.iter().enumerate() {
+            if let Some((col, c)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+                return Err(GridError::NonAscii { row, col, c });
+            }
+        }
+        let width = lines.first().map_or(0, |line| line.len());
+        if width == 0 {
+            return Err(GridError::Empty);
+        }
+        if let Some((row, line)) = lines
+            .iter()
+            .enumerate()
+            .find(|(_, line)| line.len() != width)
+        {
+            return Err(GridError::Ragged {
+                row,
+                len: line.len(),
+                width,
+            });
+        }
 
         Ok(Self {
-            inner,
+            inner: lines.concat().into_bytes(),
             width,
-            height,
+            syntax: NumberSyntax::default(),
         })
     }
 }
 
-fn number_of_places(n: usize) -> usize {
-    if n < 10 {
-        return 1;
-    };
-    1 + number_of_places(n / 10)
+/// How numbers are written in a schematic. The default is the puzzle's plain
+/// decimal digits.
+#[derive(Debug, Default, Copy, Clone)]
+struct NumberSyntax {
+    // a `+` or `-` right before the digits is part of the number
+    signed: bool,
+    // `0x` followed by hex digits is a number
+    hex: bool,
+    // a number reaching the end of a row carries on at the start of the next
+    wrap: bool,
 }
 
-fn digit_index(s: &str) -> IResult<&str, NumberLine> {
-    let (digit, taken) = take_till(|c: char| c.is_ascii_digit())(s)?;
-    let (next, d) = map_res(digit1, str::parse)(digit)?;
-    Ok((
-        next,
-        NumberLine {
-            origin: taken.len(),
-            num: d,
-        },
-    ))
+impl NumberSyntax {
+    /// `--signed`, `--hex=0x` and `--wrap` each turn on one extension.
+    fn from_flags(flags: &[String]) -> Result<Self, String> {
+        let mut syntax = Self::default();
+        for flag in flags {
+            match flag.as_str() {
+                "--signed" => syntax.signed = true,
+                "--hex" | "--hex=0x" => syntax.hex = true,
+                "--wrap" => syntax.wrap = true,
+                flag if flag.starts_with("--hex=") => {
+                    return Err(format!("unsupported hex prefix in {flag}, only 0x is read"))
+                }
+                flag => return Err(format!("unknown flag {flag}")),
+            }
+        }
+        Ok(syntax)
+    }
+
+    fn number<'a>(&self, s: &'a str) -> IResult<&'a str, i64> {
+        let (next, sign) = match self.signed {
+            true => opt(one_of("+-"))(s)?,
+            false => (s, None),
+        };
+        let mut decimal = map_res(digit1, str::parse::<i64>);
+        let (next, magnitude) = match self.hex {
+            true => alt((
+                preceded(
+                    tag("0x"),
+                    map_res(hex_digit1, |h| i64::from_str_radix(h, 16)),
+                ),
+                decimal,
+            ))(next)?,
+            false => decimal(next)?,
+        };
+        match sign {
+            Some('-') => Ok((next, -magnitude)),
+            _ => Ok((next, magnitude)),
+        }
+    }
+
+    /// Every number in `line`, left to right.
+    fn numbers(&self, line: &str) -> Vec<NumberLine> {
+        let mut numbers = Vec::new();
+        let mut rest = line;
+        while let Some(c) = rest.chars().next() {
+            let origin = line.len() - rest.len();
+            match self.number(rest) {
+                Ok((next, num)) => {
+                    numbers.push(NumberLine {
+                        origin,
+                        len: rest.len() - next.len(),
+                        num,
+                    });
+                    rest = next;
+                }
+                Err(_) => rest = &rest[c.len_utf8()..],
+            }
+        }
+        numbers
+    }
 }
 
 #[derive(Debug, Copy, Clone, Hash)]
 struct NumberLine {
     origin: usize,
-    num: u32,
+    // the characters taken up, which can be more than the digits of `num`
+    len: usize,
+    num: i64,
 }
 
 #[derive(Debug, Copy, Clone)]
 struct NumberRange {
-    num: u32,
+    num: i64,
+    // the first cell, the rest follow in reading order
     coord: Coord,
+    len: usize,
+    // of the grid, for numbers that wrap onto the next row
+    width: usize,
 }
 
 impl NumberRange {
-    fn places(&self) -> usize {
-        number_of_places(self.num as usize)
+    fn cells(&self) -> impl Iterator<Item = Coord> + '_ {
+        (self.coord.x..self.coord.x + self.len).map(|x| Coord {
+            x: x % self.width,
+            y: self.coord.y + x / self.width,
+        })
+    }
+
+    // every cell touching the number, including diagonally
+    fn neighbours(&self) -> impl Iterator<Item = Coord> + '_ {
+        let cells = self.cells().collect::<BTreeSet<_>>();
+        let neighbours = cells
+            .iter()
+            .flat_map(|c| {
+                let xs = c.x.saturating_sub(1)..=c.x + 1;
+                xs.flat_map(move |x| (c.y.saturating_sub(1)..=c.y + 1).map(move |y| Coord { x, y }))
+            })
+            .filter(|c| !cells.contains(c))
+            .collect::<BTreeSet<_>>();
+        neighbours.into_iter()
     }
+}
+
+/// Which numbers touch which symbols, built in one pass over the grid.
+struct PartIndex {
+    numbers: Vec<NumberRange>,
+    // every symbol and the numbers next to it, by index into `numbers`
+    symbols: BTreeMap<Coord, (char, Vec<usize>)>,
+    // the symbols next to each number
+    adjacent: Vec<Vec<Coord>>,
+}
 
-    fn range(&self) -> std::ops::Range<usize> {
-        self.coord.x..(self.coord.x + self.places())
+impl PartIndex {
+    fn new(grid: &Grid) -> Self {
+        let numbers = grid.iter_ranges().collect::<Vec<_>>();
+        // anything that isn't blank or part of a number is a symbol
+        let mut covered = vec![false; grid.inner.len()];
+        for number in &numbers {
+            for Coord { x, y } in number.cells() {
+                covered[y * grid.width + x] = true;
+            }
+        }
+        let mut symbols = grid
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, c)| c != '.' && !covered[i])
+            .map(|(i, c)| (grid.index_to_coord(&i), (c, Vec::new())))
+            .collect::<BTreeMap<_, _>>();
+        let adjacent = numbers
+            .iter()
+            .enumerate()
+            .map(|(i, number)| {
+                let adjacent = number
+                    .neighbours()
+                    .filter(|coord| symbols.contains_key(coord))
+                    .collect::<BTreeSet<_>>();
+                for coord in &adjacent {
+                    symbols.get_mut(coord).unwrap().1.push(i);
+                }
+                adjacent.into_iter().collect()
+            })
+            .collect();
+        PartIndex {
+            numbers,
+            symbols,
+            adjacent,
+        }
     }
 
-    fn intersects(&self, coord: &Coord) -> bool {
-        self.range().contains(&coord.x) && coord.y == self.coord.y
+    /// The numbers next to the symbol at `coord`.
+    fn numbers_at(&self, coord: &Coord) -> impl Iterator<Item = &NumberRange> {
+        self.symbols
+            .get(coord)
+            .into_iter()
+            .flat_map(|(_, numbers)| numbers.iter().map(|&i| &self.numbers[i]))
     }
 
-    fn is_adjacent_to(&self, coord: &Coord) -> bool {
-        let neighbours = self
-            .range()
-            .flat_map(|x| Coord { x, y: self.coord.y }.adjacent().collect::<Vec<_>>())
-            .collect::<HashSet<Coord>>();
-        neighbours.contains(coord)
+    /// The numbers next to any `symbol`, each once.
+    #[allow(dead_code)]
+    fn numbers_near(&self, symbol: char) -> impl Iterator<Item = &NumberRange> {
+        self.numbers
+            .iter()
+            .zip(&self.adjacent)
+            .filter(move |(_, adjacent)| {
+                adjacent.iter().any(|coord| self.symbols[coord].0 == symbol)
+            })
+            .map(|(number, _)| number)
     }
-}
 
-fn digit_indexes(s: &str) -> IResult<&str, Vec<NumberLine>> {
-    let (_, mut vec) = many1(digit_index)(s)?;
-    // we need to calculates the offsets for each line
-    let mut cur_offset = 0;
-    for NumberLine { origin, num } in vec.iter_mut() {
-        let next = *origin + number_of_places(*num as usize);
-        *origin += cur_offset;
-        cur_offset += next;
+    /// Numbers next to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &NumberRange> {
+        self.numbers
+            .iter()
+            .zip(&self.adjacent)
+            .filter(|(_, adjacent)| !adjacent.is_empty())
+            .map(|(number, _)| number)
     }
 
-    Ok(("", vec))
+    /// Numbers next to no symbol at all.
+    #[allow(dead_code)]
+    fn orphans(&self) -> impl Iterator<Item = &NumberRange> {
+        self.numbers
+            .iter()
+            .zip(&self.adjacent)
+            .filter(|(_, adjacent)| adjacent.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// The product of the numbers next to each `symbol` that touches exactly
+    /// `k` of them.
+    fn gear_ratios(&self, symbol: char, k: usize) -> impl Iterator<Item = (Coord, i64)> + '_ {
+        self.symbols
+            .iter()
+            .filter(move |(_, (c, numbers))| *c == symbol && numbers.len() == k)
+            .map(|(coord, (_, numbers))| {
+                let ratio = numbers.iter().map(|&i| self.numbers[i].num).product();
+                (*coord, ratio)
+            })
+    }
 }
 
-fn part_one(input: &str) -> u32 {
-    let grid = input.parse::<Grid>().unwrap();
-    grid.iter_ranges()
-        .filter_map(
-            |NumberRange {
-                 coord: Coord { x, y },
-                 num,
-             }| {
-                let is_part_number = (x..(x + number_of_places(num as usize)))
-                    .any(|x| grid.has_adjacent_symbol(&Coord { x, y }));
-                match is_part_number {
-                    true => Some(num),
-                    false => None,
-                }
-            },
-        )
-        .sum::<u32>()
-}
-
-fn part_two(s: &str) -> u32 {
-    let grid = s.parse::<Grid>().unwrap();
-    let ranges = grid.iter_ranges().collect::<Vec<_>>();
-    grid.into_iter()
-        .enumerate()
-        .filter_map(|(i, c)| {
-            let coord = grid.index_to_coord(&i);
-            match c {
-                '*' => Some(coord),
-                _ => None,
-            }
-        })
-        .filter_map(|coord| {
-            let adjacent_ranges = ranges
-                .iter()
-                .filter(|range| range.is_adjacent_to(&coord))
-                .collect::<Vec<_>>();
+fn parse_grid(input: &str, syntax: NumberSyntax) -> Grid {
+    normalize(input)
+        .parse::<Grid>()
+        .unwrap()
+        .with_syntax(syntax)
+}
 
-            match adjacent_ranges.len() {
-                2 => Some(adjacent_ranges[0].num * adjacent_ranges[1].num),
-                _ => None,
-            }
-        })
+fn part_one(input: &str) -> i64 {
+    sum_part_numbers(input, NumberSyntax::default())
+}
+
+fn sum_part_numbers(input: &str, syntax: NumberSyntax) -> i64 {
+    PartIndex::new(&parse_grid(input, syntax))
+        .part_numbers()
+        .map(|range| range.num)
+        .sum()
+}
+
+fn part_two(s: &str) -> i64 {
+    sum_gear_ratios(s, NumberSyntax::default())
+}
+
+fn sum_gear_ratios(input: &str, syntax: NumberSyntax) -> i64 {
+    PartIndex::new(&parse_grid(input, syntax))
+        .gear_ratios('*', 2)
+        .map(|(_, ratio)| ratio)
         .sum()
 }
 
+fn explain(input: &str, syntax: NumberSyntax) -> Trace {
+    let grid = parse_grid(input, syntax);
+    let index = PartIndex::new(&grid);
+    let mut trace = Trace::new();
+
+    for (range, adjacent) in index.numbers.iter().zip(&index.adjacent) {
+        let item = format!("{} at ({}, {})", range.num, range.coord.x, range.coord.y);
+        match adjacent.first() {
+            Some(coord) => {
+                let c = index.symbols[coord].0;
+                trace
+                    .step(1, item, "part number")
+                    .detail("symbol", format!("'{c}' at ({}, {})", coord.x, coord.y));
+            }
+            None => {
+                trace.step(1, item, "not a part number");
+            }
+        }
+    }
+
+    for (coord, (c, _)) in &index.symbols {
+        if *c != '*' {
+            continue;
+        }
+        let adjacent = index
+            .numbers_at(coord)
+            .map(|range| range.num)
+            .collect::<Vec<_>>();
+        let verdict = match adjacent.as_slice() {
+            [a, b] => format!("gear ratio {}", a * b),
+            _ => "not a gear".to_owned(),
+        };
+        trace
+            .step(2, format!("'*' at ({}, {})", coord.x, coord.y), verdict)
+            .detail("numbers", format!("{adjacent:?}"));
+    }
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,5 +477,221 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(include_str!("../test")), 467835);
     }
-}
 
+    #[test]
+    fn test_non_square() {
+        let wide = "467..114..12\n...*......**";
+        let grid = wide.parse::<Grid>().unwrap();
+        assert_eq!(grid.width, 12);
+        assert_eq!(
+            grid.iter_lines().collect::<Vec<_>>(),
+            wide.lines().collect::<Vec<_>>()
+        );
+        assert_eq!(part_one(wide), 467 + 12);
+
+        let tall = "1.\n*.\n2.\n..\n3#\n.4";
+        assert_eq!(part_one(tall), 1 + 2 + 3 + 4);
+        assert_eq!(part_two(tall), 2);
+    }
+
+    #[test]
+    fn test_ragged() {
+        assert_eq!(
+            "467..\n..*\n35...".parse::<Grid>().unwrap_err(),
+            GridError::Ragged {
+                row: 1,
+                len: 3,
+                width: 5
+            }
+        );
+        assert_eq!("".parse::<Grid>().unwrap_err(), GridError::Empty);
+        assert_eq!(
+            "12.\n1é.".parse::<Grid>().unwrap_err(),
+            GridError::NonAscii {
+                row: 1,
+                col: 1,
+                c: 'é'
+            }
+        );
+        assert_eq!("\n12".parse::<Grid>().unwrap_err(), GridError::Empty);
+        assert!("12.\n..*\n".parse::<Grid>().is_ok());
+    }
+
+    #[test]
+    fn test_index() {
+        let grid = include_str!("../test").parse::<Grid>().unwrap();
+        let index = PartIndex::new(&grid);
+        let nums = |numbers: &mut dyn Iterator<Item = &NumberRange>| {
+            numbers.map(|range| range.num).collect::<Vec<_>>()
+        };
+        assert_eq!(nums(&mut index.numbers_near('#')), [633]);
+        assert_eq!(nums(&mut index.numbers_near('*')), [467, 35, 617, 755, 598]);
+        assert_eq!(nums(&mut index.orphans()), [114, 58]);
+        assert_eq!(
+            nums(&mut index.numbers_at(&Coord { x: 5, y: 8 })),
+            [755, 598]
+        );
+        assert_eq!(
+            index.gear_ratios('*', 1).collect::<Vec<_>>(),
+            [(Coord { x: 3, y: 4 }, 617)]
+        );
+        assert_eq!(index.gear_ratios('#', 2).count(), 0);
+
+        // a long number touching a symbol from several cells counts once
+        let grid = "123\n.*.".parse::<Grid>().unwrap();
+        let index = PartIndex::new(&grid);
+        assert_eq!(nums(&mut index.numbers_at(&Coord { x: 1, y: 1 })), [123]);
+        assert_eq!(
+            index.gear_ratios('*', 1).next(),
+            Some((Coord { x: 1, y: 1 }, 123))
+        );
+    }
+
+    #[test]
+    fn test_number_syntax() {
+        let plain = NumberSyntax::default();
+        let signed = NumberSyntax {
+            signed: true,
+            ..plain
+        };
+        let hex = NumberSyntax { hex: true, ..plain };
+        let spans = |syntax: NumberSyntax, line| {
+            syntax
+                .numbers(line)
+                .iter()
+                .map(|n| (n.origin, n.len, n.num))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(plain, "..007.-5"), [(2, 3, 7), (7, 1, 5)]);
+        assert_eq!(
+            spans(signed, "..007.-5+2"),
+            [(2, 3, 7), (6, 2, -5), (8, 2, 2)]
+        );
+        assert_eq!(spans(plain, "0x1F"), [(0, 1, 0), (2, 1, 1)]);
+        assert_eq!(
+            spans(hex, "0x1F.0x.12"),
+            [(0, 4, 31), (5, 1, 0), (8, 2, 12)]
+        );
+
+        // the leading zeros are part of the number and next to the symbol
+        assert_eq!(part_one("007..\n....*"), 0);
+        assert_eq!(part_one("007..\n...*."), 7);
+
+        let grid = |syntax| {
+            "..-12...\n....*0xA\n$.......\n.-3....."
+                .parse::<Grid>()
+                .unwrap()
+                .with_syntax(syntax)
+        };
+        let sum = |syntax| -> i64 {
+            PartIndex::new(&grid(syntax))
+                .part_numbers()
+                .map(|n| n.num)
+                .sum()
+        };
+        // read as plain digits the signs and the hex prefix are symbols
+        assert_eq!(sum(plain), 12 + 3);
+        assert_eq!(sum(signed), -12 - 3);
+        assert_eq!(sum(hex), 12 + 10 + 3);
+        assert_eq!(
+            sum(NumberSyntax {
+                signed: true,
+                hex: true,
+                wrap: false
+            }),
+            -12 + 10 - 3
+        );
+    }
+
+    #[test]
+    fn test_wrapping_numbers() {
+        let wrap = NumberSyntax {
+            wrap: true,
+            ..NumberSyntax::default()
+        };
+        let index =
+            |s: &str, syntax| PartIndex::new(&s.parse::<Grid>().unwrap().with_syntax(syntax));
+        let nums = |index: &PartIndex| index.numbers.iter().map(|n| n.num).collect::<Vec<_>>();
+
+        let schematic = "...12\n34...\n.....\n*...5\n6....";
+        assert_eq!(
+            nums(&index(schematic, NumberSyntax::default())),
+            [12, 34, 5, 6]
+        );
+        let wrapped = index(schematic, wrap);
+        assert_eq!(nums(&wrapped), [1234, 56]);
+        let cells = wrapped.numbers[0].cells().collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            [
+                Coord { x: 3, y: 0 },
+                Coord { x: 4, y: 0 },
+                Coord { x: 0, y: 1 },
+                Coord { x: 1, y: 1 }
+            ]
+        );
+        // both halves of 56 are next to the `*`, so it counts once
+        assert_eq!(
+            wrapped.gear_ratios('*', 1).collect::<Vec<_>>(),
+            [(Coord { x: 0, y: 3 }, 56)]
+        );
+        assert_eq!(wrapped.orphans().map(|n| n.num).collect::<Vec<_>>(), [1234]);
+
+        let wrapped = index("..#12\n34...", wrap);
+        assert_eq!(
+            wrapped.part_numbers().map(|n| n.num).collect::<Vec<_>>(),
+            [1234]
+        );
+        // neighbours don't wrap, the `#` under the 1 is two rows from it
+        let wrapped = index("....1\n2....\n....#", wrap);
+        assert_eq!(wrapped.part_numbers().count(), 0);
+
+        // a sign at the end of a row belongs to the number it starts
+        let signed = NumberSyntax {
+            signed: true,
+            ..wrap
+        };
+        assert_eq!(nums(&index("..*.-\n12...", signed)), [-12]);
+    }
+
+    #[test]
+    fn test_syntax_flags() {
+        let flags = |flags: &[&str]| {
+            NumberSyntax::from_flags(&flags.iter().map(|f| f.to_string()).collect::<Vec<_>>())
+        };
+        let syntax = flags(&["--signed", "--hex=0x"]).unwrap();
+        assert!(syntax.signed && syntax.hex && !syntax.wrap);
+        assert!(flags(&["--wrap"]).unwrap().wrap);
+        assert!(flags(&["--hex=$"]).is_err());
+        assert!(flags(&["--wrapped"]).is_err());
+        assert_eq!(
+            sum_part_numbers("-12*\n0x1f", flags(&["--signed", "--hex"]).unwrap()),
+            19
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let trace = explain(include_str!("../test"), NumberSyntax::default());
+        let not_parts = trace
+            .steps()
+            .filter(|step| step.verdict() == "not a part number")
+            .map(|step| step.item())
+            .collect::<Vec<_>>();
+        assert_eq!(not_parts, ["114 at (5, 0)", "58 at (7, 5)"]);
+        assert!(trace
+            .to_text()
+            .contains("[part 1] 467 at (0, 0): part number\n    symbol: '*' at (3, 1)\n"));
+        assert!(trace
+            .to_text()
+            .contains("[part 2] '*' at (3, 4): not a gear\n    numbers: [617]\n"));
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 4361);
+            assert_eq!(part_two(&input), 467835);
+        }
+    }
+}
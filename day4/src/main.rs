@@ -1,41 +1,198 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    str::FromStr,
+};
 
+use common::{cli::Args, input::normalize};
 use nom::{
-    bytes::complete::{tag, take_till, take_until},
+    bytes::complete::{tag, take_till},
     character::complete::digit1,
-    character::complete::u32,
     combinator::map_res,
     multi::many1,
-    sequence::{delimited, separated_pair},
+    sequence::separated_pair,
     IResult,
 };
 
+/// `--provenance=csv` or `--provenance=dot` prints the provenance report for
+/// part two instead of the answers. `--scoring=` and `--copies=` change the
+/// rules, see `Scoring` and `CopyRule` for their names.
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let (args, flags) = Args::parse_with(&["--provenance", "--scoring", "--copies"]);
+    let mut provenance = None;
+    let mut scoring = None;
+    let mut rule = None;
+    for flag in &flags {
+        let known = match flag.split_once('=') {
+            Some(("--provenance", format @ ("csv" | "dot"))) => {
+                provenance = Some(format);
+                true
+            }
+            Some(("--scoring", name)) => name.parse().map(|s| scoring = Some(s)).is_ok(),
+            Some(("--copies", name)) => name.parse().map(|r| rule = Some(r)).is_ok(),
+            _ => false,
+        };
+        if !known {
+            eprintln!("unknown flag {flag}");
+            std::process::exit(2)
+        }
+    }
+    let input = args.read_input(include_str!("../input"));
+    if let Some(format) = provenance {
+        let mut q = CardsQueue::from_str(&normalize(&input))
+            .unwrap()
+            .with_rule(rule.unwrap_or(CopyRule::NextMatches));
+        let report = q.provenance(&scoring.unwrap_or(Scoring::Doubling));
+        match format {
+            "csv" => print!("{}", report.to_csv()),
+            _ => print!("{}", report.to_dot()),
+        }
+        return;
+    }
+    let cache = args.cache(4, include_str!("main.rs"));
+    if scoring.is_none() && rule.is_none() {
+        println!("{}", cache.get_or_solve(1, &input, part_one));
+        println!("{}", cache.get_or_solve(2, &input, part_two));
+        return;
+    }
+    let cache = cache.keyed(&format!("{scoring:?} {rule:?}"));
+    let scoring = scoring.unwrap_or(Scoring::Doubling);
+    let rule = rule.unwrap_or(CopyRule::NextMatches);
+    println!(
+        "{}",
+        cache.get_or_solve(1, &input, |input| total_score(input, &scoring))
+    );
+    println!(
+        "{}",
+        cache.get_or_solve(2, &input, |input| total_copies(input, rule))
+    );
 }
 
 #[derive(Clone, Debug)]
 struct Card {
     id: usize,
-    winners: Vec<usize>,
+    winners: BTreeSet<usize>,
     members: Vec<usize>,
 }
 
 impl Card {
-    fn count_winners(&self) -> usize {
+    /// The numbers we have that are winners, in the order we have them.
+    fn matches(&self) -> impl Iterator<Item = usize> + '_ {
         self.members
             .iter()
-            .fold(0, |acc, cur| match self.winners.contains(cur) {
-                true => acc + 1,
-                false => acc,
-            })
+            .copied()
+            .filter(|n| self.winners.contains(n))
+    }
+
+    fn count_winners(&self) -> usize {
+        self.matches().count()
+    }
+
+    fn score_card(&self) -> u128 {
+        Scoring::Doubling.score(self.count_winners())
+    }
+}
+
+/// Points for a card with some number of matches.
+#[derive(Clone, Debug, PartialEq)]
+enum Scoring {
+    /// 1 for the first match, doubled for each one after.
+    Doubling,
+    /// 1 for each match.
+    Linear,
+    /// 1, 1, 2, 3, 5, ... for 1, 2, 3, 4, 5, ... matches.
+    Fibonacci,
+    /// Points for 0, 1, 2, ... matches. Matches beyond the end of the table
+    /// score its last entry.
+    Table(Vec<u128>),
+}
+
+impl Scoring {
+    fn score(&self, matches: usize) -> u128 {
+        let points = match (self, matches) {
+            (Scoring::Table(table), n) => table.get(n).or(table.last()).copied(),
+            (_, 0) => Some(0),
+            (Scoring::Doubling, n) => u32::try_from(n - 1)
+                .ok()
+                .and_then(|shift| 1u128.checked_shl(shift)),
+            (Scoring::Linear, n) => Some(n as u128),
+            (Scoring::Fibonacci, n) => (1..n)
+                .try_fold((1u128, 0u128), |(a, b), _| Some((a.checked_add(b)?, a)))
+                .map(|(a, _)| a),
+        };
+        match self {
+            Scoring::Table(_) => points.unwrap_or(0),
+            _ => points.expect("more points than fit in a u128"),
+        }
     }
+}
+
+/// Which later cards a card wins copies of.
+#[derive(Clone, Debug, PartialEq)]
+enum CopyRule {
+    /// One of each of the next cards, as many as there are matches.
+    NextMatches,
+    /// One of each of the next `n` cards, if there is at least one match.
+    Next(usize),
+    /// For each matching number, the card that many places on.
+    MatchedOffsets,
+}
 
-    fn score_card(&self) -> usize {
-        match self.count_winners() {
-            0 => 0,
-            n => 2usize.pow(n as u32 - 1),
+impl CopyRule {
+    /// Indices of the copies won by the card at `index` of `len`, all after
+    /// it. Cards past the end are not won.
+    fn targets(&self, index: usize, card: &Card, len: usize) -> Vec<usize> {
+        let next = |n: usize| (index + 1..(index + 1 + n).min(len)).collect();
+        match self {
+            CopyRule::NextMatches => next(card.count_winners()),
+            CopyRule::Next(n) => match card.count_winners() {
+                0 => vec![],
+                _ => next(*n),
+            },
+            CopyRule::MatchedOffsets => card
+                .matches()
+                .filter(|&offset| offset > 0)
+                .map(|offset| index + offset)
+                .filter(|&i| i < len)
+                .collect(),
+        }
+    }
+}
+
+/// `doubling`, `linear`, `fibonacci` or `table:` followed by the points for
+/// 0, 1, 2, ... matches separated by commas.
+impl FromStr for Scoring {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "fibonacci" => Ok(Scoring::Fibonacci),
+            _ => match s.strip_prefix("table:") {
+                Some(table) => table
+                    .split(',')
+                    .map(|points| points.trim().parse().map_err(|_| ()))
+                    .collect::<Result<_, _>>()
+                    .map(Scoring::Table),
+                None => Err(()),
+            },
+        }
+    }
+}
+
+/// `matches`, `next:` followed by a number of cards, or `offsets`.
+impl FromStr for CopyRule {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "matches" => Ok(CopyRule::NextMatches),
+            "offsets" => Ok(CopyRule::MatchedOffsets),
+            _ => match s.strip_prefix("next:").map(str::parse) {
+                Some(Ok(n)) => Ok(CopyRule::Next(n)),
+                _ => Err(()),
+            },
         }
     }
 }
@@ -58,7 +215,7 @@ fn parse_card(s: &str) -> IResult<&str, Card> {
     Ok((
         "",
         Card {
-            winners,
+            winners: winners.into_iter().collect(),
             members,
             id,
         },
@@ -76,55 +233,194 @@ impl FromStr for Card {
     }
 }
 
-fn part_one(input: &str) -> usize {
-    input
+fn part_one(input: &str) -> u128 {
+    normalize(input)
         .lines()
         .map(|l| Card::from_str(l).unwrap().score_card())
         .sum()
 }
 
+/// The cards and how many copies of each are held once every win is paid
+/// out. Card ids run from 1 in order, which parsing checks.
 struct CardsQueue {
     original: Vec<Card>,
+    copies: Vec<u128>,
+    rule: CopyRule,
 }
 
 impl CardsQueue {
+    fn with_rule(self, rule: CopyRule) -> Self {
+        CardsQueue { rule, ..self }
+    }
+
+    /// Points for every card, one copy each.
+    fn score(&self, scoring: &Scoring) -> u128 {
+        self.original
+            .iter()
+            .map(|card| scoring.score(card.count_winners()))
+            .try_fold(0u128, |total, points| total.checked_add(points))
+            .expect("more points than fit in a u128")
+    }
+
+    // copies only ever go to later cards, so one pass settles every count
     fn process(&mut self) {
-        let original = self.original.len();
-        for i in 1..=original {
-            let to_add = self
-                .original
+        self.copies = vec![1; self.original.len()];
+        for (i, card) in self.original.iter().enumerate() {
+            let won = self.copies[i];
+            for j in self.rule.targets(i, card, self.original.len()) {
+                self.copies[j] = self.copies[j]
+                    .checked_add(won)
+                    .expect("more copies than fit in a u128");
+            }
+        }
+    }
+
+    /// Why each card has the copies it has.
+    fn provenance(&mut self, scoring: &Scoring) -> Report {
+        self.process();
+        let mut sources = vec![BTreeMap::new(); self.original.len()];
+        for (i, card) in self.original.iter().enumerate() {
+            for j in self.rule.targets(i, card, self.original.len()) {
+                *sources[j].entry(card.id).or_insert(0) += self.copies[i];
+            }
+        }
+        let cards = self
+            .original
+            .iter()
+            .zip(&self.copies)
+            .zip(sources)
+            .map(|((card, &copies), sources)| Provenance {
+                id: card.id,
+                matches: card.count_winners(),
+                score: scoring.score(card.count_winners()),
+                copies,
+                sources: sources.into_iter().collect(),
+            })
+            .collect();
+        Report { cards }
+    }
+
+    #[allow(dead_code)]
+    fn count_id(&self, id: usize) -> u128 {
+        self.copies[id - 1]
+    }
+
+    fn total(&self) -> u128 {
+        self.copies
+            .iter()
+            .try_fold(0u128, |total, &copies| total.checked_add(copies))
+            .expect("more copies than fit in a u128")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Provenance {
+    id: usize,
+    matches: usize,
+    score: u128,
+    copies: u128,
+    // the cards that won copies of this one and how many each won
+    sources: Vec<(usize, u128)>,
+}
+
+struct Report {
+    cards: Vec<Provenance>,
+}
+
+impl Report {
+    /// One row per card, with sources written as `card:copies` separated by
+    /// spaces.
+    fn to_csv(&self) -> String {
+        let mut out = String::from("id,matches,score,copies,sources\n");
+        for card in &self.cards {
+            let sources = card
+                .sources
                 .iter()
-                .filter(|c| c.id == i)
-                .flat_map(|card| {
-                    let winners = card.count_winners();
-                    (i..winners + i).map(|idx| self.original.get(idx).unwrap().clone())
-                })
-                .collect::<Vec<_>>();
-            self.original.extend(to_add)
+                .map(|(id, copies)| format!("{id}:{copies}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{}",
+                card.id, card.matches, card.score, card.copies, sources
+            );
         }
+        out
     }
 
-    fn count_id(&self, id: usize) -> usize {
-        self.original.iter().filter(|c| c.id == id).count()
+    /// A Graphviz digraph with an edge from each card to the cards it won,
+    /// labelled with the copies won.
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph cards {\n");
+        for card in &self.cards {
+            let _ = writeln!(
+                out,
+                "    {} [label=\"Card {}\\n{} copies\"];",
+                card.id, card.id, card.copies
+            );
+        }
+        for card in &self.cards {
+            for (source, copies) in &card.sources {
+                let _ = writeln!(out, "    {} -> {} [label=\"{}\"];", source, card.id, copies);
+            }
+        }
+        out.push_str("}\n");
+        out
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum CardsError {
+    // the line, from 1, isn't a card
+    Unparsed(usize),
+    // a card's id isn't one more than the card before it
+    OutOfOrder { expected: usize, found: usize },
+}
+
 impl FromStr for CardsQueue {
-    type Err = ();
+    type Err = CardsError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let original = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| Card::from_str(l).map_err(|_| CardsError::Unparsed(i + 1)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some((i, card)) = original
+            .iter()
+            .enumerate()
+            .find(|(i, card)| card.id != i + 1)
+        {
+            return Err(CardsError::OutOfOrder {
+                expected: i + 1,
+                found: card.id,
+            });
+        }
         Ok(Self {
-            original: s
-                .lines()
-                .map(|l| Card::from_str(l).unwrap())
-                .collect::<Vec<_>>(),
+            copies: vec![1; original.len()],
+            original,
+            rule: CopyRule::NextMatches,
         })
     }
 }
 
-fn part_two(s: &str) -> usize {
-    let mut q = CardsQueue::from_str(s).unwrap();
+/// Part one with other points per card.
+fn total_score(input: &str, scoring: &Scoring) -> u128 {
+    CardsQueue::from_str(&normalize(input))
+        .unwrap()
+        .score(scoring)
+}
+
+fn part_two(s: &str) -> u128 {
+    total_copies(s, CopyRule::NextMatches)
+}
+
+/// Part two with other copies won per card.
+fn total_copies(input: &str, rule: CopyRule) -> u128 {
+    let mut q = CardsQueue::from_str(&normalize(input))
+        .unwrap()
+        .with_rule(rule);
     q.process();
-    q.original.len()
+    q.total()
 }
 
 #[cfg(test)]
@@ -141,6 +437,14 @@ mod tests {
         assert_eq!(part_two(include_str!("../test2")), 30);
     }
 
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test2")) {
+            assert_eq!(part_one(&input), 13);
+            assert_eq!(part_two(&input), 30);
+        }
+    }
+
     #[test]
     fn test_number_cards() {
         let mut q = CardsQueue::from_str(include_str!("../test2")).unwrap();
@@ -152,5 +456,158 @@ mod tests {
         assert_eq!(q.count_id(4), 8);
         assert_eq!(q.count_id(5), 14);
     }
-}
 
+    #[test]
+    fn test_scoring() {
+        let scores = |scoring: Scoring| (0..7).map(|n| scoring.score(n)).collect::<Vec<_>>();
+        assert_eq!(scores(Scoring::Doubling), [0, 1, 2, 4, 8, 16, 32]);
+        assert_eq!(scores(Scoring::Linear), [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(scores(Scoring::Fibonacci), [0, 1, 1, 2, 3, 5, 8]);
+        assert_eq!(
+            scores(Scoring::Table(vec![0, 10, 15])),
+            [0, 10, 15, 15, 15, 15, 15]
+        );
+        assert_eq!(scores(Scoring::Table(vec![])), [0; 7]);
+
+        let q = CardsQueue::from_str(include_str!("../test")).unwrap();
+        assert_eq!(q.score(&Scoring::Doubling), 13);
+        // matches are 4, 2, 2, 1, 0, 0
+        assert_eq!(q.score(&Scoring::Linear), 9);
+        assert_eq!(q.score(&Scoring::Fibonacci), 3 + 1 + 1 + 1);
+    }
+
+    #[test]
+    fn test_most_points() {
+        assert_eq!(Scoring::Doubling.score(128), 1 << 127);
+        assert!(Scoring::Fibonacci.score(186) > Scoring::Fibonacci.score(185));
+        assert_eq!(Scoring::Linear.score(1000), 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "more points than fit in a u128")]
+    fn test_doubling_overflow() {
+        Scoring::Doubling.score(129);
+    }
+
+    #[test]
+    #[should_panic(expected = "more points than fit in a u128")]
+    fn test_fibonacci_overflow() {
+        Scoring::Fibonacci.score(187);
+    }
+
+    #[test]
+    fn test_rule_names() {
+        assert_eq!("linear".parse(), Ok(Scoring::Linear));
+        assert_eq!(
+            "table:0, 10,15".parse(),
+            Ok(Scoring::Table(vec![0, 10, 15]))
+        );
+        assert_eq!("table:".parse::<Scoring>(), Err(()));
+        assert_eq!("squares".parse::<Scoring>(), Err(()));
+        assert_eq!("next:3".parse(), Ok(CopyRule::Next(3)));
+        assert_eq!("offsets".parse(), Ok(CopyRule::MatchedOffsets));
+        assert_eq!("next:".parse::<CopyRule>(), Err(()));
+
+        let input = include_str!("../test");
+        assert_eq!(total_score(input, &Scoring::Doubling), part_one(input));
+        assert_eq!(total_score(input, &Scoring::Linear), 9);
+        assert_eq!(
+            total_copies(input, CopyRule::Next(1)),
+            1 + 2 + 3 + 4 + 5 + 1
+        );
+    }
+
+    #[test]
+    fn test_card_ids() {
+        let cards = |s: &str| CardsQueue::from_str(s).map(|q| q.original.len());
+        assert_eq!(cards("Card 1: 1 | 1\nCard 2: 2 | 3"), Ok(2));
+        assert_eq!(
+            cards("Card 1: 1 | 1\nCard 3: 2 | 3"),
+            Err(CardsError::OutOfOrder {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            cards("Card 2: 1 | 1"),
+            Err(CardsError::OutOfOrder {
+                expected: 1,
+                found: 2
+            })
+        );
+        assert_eq!(
+            cards("Card 1: 1 | 1\nCard 2: 1 |"),
+            Err(CardsError::Unparsed(2))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "more copies than fit in a u128")]
+    fn test_total_overflow() {
+        let mut q = CardsQueue::from_str("Card 1: 1 | 1\nCard 2: 1 | 1").unwrap();
+        q.copies = vec![u128::MAX, 1];
+        q.total();
+    }
+
+    #[test]
+    fn test_copy_rules() {
+        let copies = |rule| {
+            let mut q = CardsQueue::from_str(include_str!("../test"))
+                .unwrap()
+                .with_rule(rule);
+            q.process();
+            q.copies
+        };
+        assert_eq!(copies(CopyRule::NextMatches), [1, 2, 4, 8, 14, 1]);
+        assert_eq!(copies(CopyRule::Next(1)), [1, 2, 3, 4, 5, 1]);
+        // card 1 matches 48, 83, 86, 17, so no card is in reach
+        // card 2 matches 32, 61; card 3 matches 1, 21; card 4 matches 84
+        assert_eq!(copies(CopyRule::MatchedOffsets), [1, 1, 1, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_provenance() {
+        // counted without calling `process` first
+        let mut q = CardsQueue::from_str(include_str!("../test2")).unwrap();
+        let report = q.provenance(&Scoring::Doubling);
+        assert_eq!(report.to_csv(), include_str!("../test2.csv"));
+        for card in &report.cards {
+            let won = card.sources.iter().map(|(_, copies)| copies).sum::<u128>();
+            assert_eq!(card.copies, 1 + won);
+            assert_eq!(card.copies, q.count_id(card.id));
+        }
+
+        let dot = report.to_dot();
+        assert!(dot.starts_with("digraph cards {\n    1 [label=\"Card 1\\n1 copies\"];\n"));
+        assert!(dot.contains("    3 -> 5 [label=\"4\"];\n"));
+        assert_eq!(dot.matches(" -> ").count(), 9);
+    }
+
+    fn generate(cards: usize, matches: usize) -> String {
+        let numbers = (1..=matches)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (1..=cards)
+            .map(|id| format!("Card {id}: {numbers} | {numbers}\n"))
+            .collect()
+    }
+
+    #[test]
+    fn test_many_copies() {
+        // each card wins one copy of the next, so card k ends up with k
+        let n = 5000;
+        assert_eq!(part_two(&generate(n, 1)), (n * (n + 1) / 2) as u128);
+
+        // winning two copies grows like Fibonacci, well past u64
+        let mut q = CardsQueue::from_str(&generate(150, 2)).unwrap();
+        q.process();
+        let mut expected = vec![1u128, 2];
+        for k in 2..150 {
+            expected.push(1 + expected[k - 1] + expected[k - 2]);
+        }
+        assert_eq!(q.count_id(150), expected[149]);
+        assert!(q.count_id(150) > u64::MAX as u128);
+        assert_eq!(q.total(), expected.iter().sum::<u128>());
+    }
+}
@@ -1,15 +1,18 @@
-use std::{collections::HashMap, ops::RangeBounds};
+use std::collections::BTreeSet;
 
+use common::{cli::Args, input::normalize};
 use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::{tag, take_till, take_while1},
     character::complete::digit1,
-    combinator::{map_res, opt, value},
+    combinator::{map_res, opt},
     multi::many1,
-    sequence::{separated_pair, tuple},
+    sequence::{separated_pair, terminated, tuple},
     IResult,
 };
 
+/// A run of consecutive values, end exclusive.
+type Interval = std::ops::Range<usize>;
+
 trait Parse: Sized {
     fn parse(s: &str) -> IResult<&str, Self>;
 }
@@ -22,6 +25,10 @@ struct SeedRange {
 
 impl SeedRange {
     fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.interval()
+    }
+
+    fn interval(&self) -> Interval {
         self.start..self.start + self.end
     }
 }
@@ -35,42 +42,31 @@ impl Parse for SeedRange {
 }
 
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Attribute {
-    Seed,
-    Soil,
-    Fertilizer,
-    Water,
-    Light,
-    Temperature,
-    Humidity,
-    Location,
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(5, include_str!("main.rs"));
+    if let Ok((_, almanac)) = Almanac::parse(&normalize(&input)) {
+        if let Err(errors) = almanac.validate() {
+            for error in errors {
+                eprintln!("error: {error}");
+            }
+            std::process::exit(1);
+        }
+    }
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
-impl Parse for Attribute {
-    fn parse(s: &str) -> IResult<&str, Attribute> {
-        alt((
-            value(Attribute::Seed, tag("seed")),
-            value(Attribute::Soil, tag("soil")),
-            value(Attribute::Fertilizer, tag("fertilizer")),
-            value(Attribute::Water, tag("water")),
-            value(Attribute::Light, tag("light")),
-            value(Attribute::Temperature, tag("temperature")),
-            value(Attribute::Humidity, tag("humidity")),
-            value(Attribute::Location, tag("location")),
-        ))(s)
-    }
+/// A category name such as `seed` or `soil`.
+fn category(s: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(s)
 }
 
 #[derive(Debug)]
 struct Map {
     link: Vec<Range>,
-    from: Attribute,
-    to: Attribute,
+    from: String,
+    to: String,
 }
 
 #[derive(Debug)]
@@ -96,6 +92,10 @@ impl Parse for Range {
 }
 
 impl Range {
+    fn source_interval(&self) -> Interval {
+        self.source..self.source + self.range
+    }
+
     fn get(&self, val: &usize) -> Option<usize> {
         match (self.source..self.source + self.range).contains(val) {
             true => {
@@ -114,32 +114,375 @@ impl Map {
             .find_map(|range| range.get(&key))
             .unwrap_or(key)
     }
+
+    /// Maps every value in `interval` at once, split wherever it crosses
+    /// the edge of a range. Parts that no range covers map to themselves.
+    fn get_interval(&self, interval: &Interval) -> Vec<Interval> {
+        let mut out = Vec::new();
+        let mut cur = interval.start;
+        // `link` is sorted by source
+        for range in &self.link {
+            let source = range.source..range.source + range.range;
+            if source.end <= cur {
+                continue;
+            }
+            if source.start >= interval.end {
+                break;
+            }
+            if source.start > cur {
+                out.push(cur..source.start);
+                cur = source.start;
+            }
+            let end = source.end.min(interval.end);
+            out.push(
+                range.destination + (cur - range.source)..range.destination + (end - range.source),
+            );
+            cur = end;
+        }
+        if cur < interval.end {
+            out.push(cur..interval.end);
+        }
+        out
+    }
+
+    /// Every key that maps to `value`: itself if no range covers it, and one
+    /// for each range whose destination holds it.
+    fn invert(&self, value: usize) -> Vec<usize> {
+        let passes_through = !self.link.iter().any(|range| range.get(&value).is_some());
+        let mut keys = self
+            .link
+            .iter()
+            .filter(|range| (range.destination..range.destination + range.range).contains(&value))
+            .map(|range| range.source + (value - range.destination))
+            .collect::<Vec<_>>();
+        if passes_through {
+            keys.push(value);
+        }
+        keys
+    }
+
+    /// Every interval of keys that maps into `interval`.
+    fn invert_interval(&self, interval: &Interval) -> Vec<Interval> {
+        let mut out = self
+            .link
+            .iter()
+            .filter_map(|range| {
+                let start = interval.start.max(range.destination);
+                let end = interval.end.min(range.destination + range.range);
+                (start < end).then(|| {
+                    range.source + (start - range.destination)
+                        ..range.source + (end - range.destination)
+                })
+            })
+            .collect::<Vec<_>>();
+        // and the gaps between sources, which pass through unchanged
+        let mut cur = interval.start;
+        for range in &self.link {
+            if range.source >= interval.end {
+                break;
+            }
+            if range.source > cur {
+                out.push(cur..range.source);
+            }
+            cur = cur.max(range.source + range.range);
+        }
+        if cur < interval.end {
+            out.push(cur..interval.end);
+        }
+        out
+    }
+}
+
+/// Sorts `intervals` and joins any that overlap or touch.
+fn merge(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.retain(|interval| !interval.is_empty());
+    intervals.sort_by_key(|interval| interval.start);
+    let mut merged: Vec<Interval> = Vec::new();
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// A whole map as one function over every value: sorted pieces, each
+/// shifting the values from its start up to the next piece's start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Piecewise {
+    // (source start, destination start), the first starting at 0
+    pieces: Vec<(usize, usize)>,
+}
+
+impl Piecewise {
+    fn identity() -> Self {
+        Piecewise {
+            pieces: vec![(0, 0)],
+        }
+    }
+
+    fn from_map(map: &Map) -> Self {
+        let mut pieces = Vec::new();
+        let mut cur = 0;
+        // `link` is sorted by source, and the first of any overlap wins
+        for range in &map.link {
+            let end = range.source + range.range;
+            if end <= cur {
+                continue;
+            }
+            if range.source > cur {
+                pieces.push((cur, cur));
+            }
+            let start = cur.max(range.source);
+            pieces.push((start, range.destination + (start - range.source)));
+            cur = end;
+        }
+        pieces.push((cur, cur));
+        Piecewise { pieces }.simplify()
+    }
+
+    fn end(&self, i: usize) -> usize {
+        self.pieces
+            .get(i + 1)
+            .map_or(usize::MAX, |&(start, _)| start)
+    }
+
+    /// Looks up `key` by binary search over the pieces.
+    fn get(&self, key: usize) -> usize {
+        let i = self.pieces.partition_point(|&(start, _)| start <= key) - 1;
+        let (start, destination) = self.pieces[i];
+        destination + (key - start)
+    }
+
+    /// `self` followed by `next`.
+    fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut pieces = Vec::new();
+        for (i, &(start, destination)) in self.pieces.iter().enumerate() {
+            let end = destination.saturating_add(self.end(i) - start);
+            // split where the destination crosses a piece of `next`
+            let first = next.pieces.partition_point(|&(s, _)| s <= destination) - 1;
+            for j in first..next.pieces.len() {
+                let (next_start, next_destination) = next.pieces[j];
+                let from = destination.max(next_start);
+                if from >= end {
+                    break;
+                }
+                pieces.push((
+                    start + (from - destination),
+                    next_destination + (from - next_start),
+                ));
+            }
+        }
+        Piecewise { pieces }.simplify()
+    }
+
+    // joins pieces that carry on where the previous one stopped
+    fn simplify(self) -> Self {
+        let mut pieces: Vec<(usize, usize)> = Vec::new();
+        for (start, destination) in self.pieces {
+            match pieces.last() {
+                Some(&(s, d)) if d.checked_add(start - s) == Some(destination) => {}
+                _ => pieces.push((start, destination)),
+            }
+        }
+        Piecewise { pieces }
+    }
+}
+
+/// One line per piece, `source -> destination`, the last running on without
+/// end.
+impl std::fmt::Display for Piecewise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = self
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, destination))| match self.pieces.get(i + 1) {
+                Some(&(end, _)) => (
+                    format!("{start}..{end}"),
+                    format!("{destination}..{}", destination + (end - start)),
+                ),
+                None => (format!("{start}.."), format!("{destination}..")),
+            })
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(source, _)| source.len())
+            .max()
+            .unwrap_or(0);
+        for (source, destination) in rows {
+            writeln!(f, "{source:<width$} -> {destination}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AlmanacError {
+    /// Two maps read from the same category.
+    DuplicateFrom(String),
+    /// Two ranges of the map from a category read some of the same values.
+    Overlap {
+        from: String,
+        first: Interval,
+        second: Interval,
+    },
+    /// Following the maps from the first category comes back round to it.
+    Cycle(Vec<String>),
+    /// No maps lead from one category to the other.
+    MissingLink { from: String, to: String },
+}
+
+impl std::fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlmanacError::DuplicateFrom(from) => write!(f, "more than one {from} map"),
+            AlmanacError::Overlap {
+                from,
+                first,
+                second,
+            } => write!(f, "{from} map ranges {first:?} and {second:?} overlap"),
+            AlmanacError::Cycle(categories) => {
+                write!(f, "maps go round in a cycle: {}", categories.join(" -> "))
+            }
+            AlmanacError::MissingLink { from, to } => write!(f, "no maps lead from {from} to {to}"),
+        }
+    }
 }
 
 #[derive(Debug)]
 struct Almanac {
+    // the category of the seeds, where the chain of maps starts
+    category: String,
     seeds: Vec<SeedRange>,
     maps: Vec<Map>,
 }
 
 impl Almanac {
-    fn traverse(&self, to: Attribute, start: usize, cur: &Map) -> Option<usize> {
-        let next_value = cur.get(start);
-        if cur.to == to {
-            return Some(next_value);
+    fn map_from(&self, from: &str) -> Option<&Map> {
+        self.maps.iter().find(|map| map.from == from)
+    }
+
+    /// The maps to follow from `from` to `to`, in order.
+    fn path(&self, from: &str, to: &str) -> Result<Vec<&Map>, AlmanacError> {
+        let mut path = Vec::new();
+        let mut at = from;
+        while at != to {
+            let missing = || AlmanacError::MissingLink {
+                from: from.to_owned(),
+                to: to.to_owned(),
+            };
+            let map = self.map_from(at).ok_or_else(missing)?;
+            if path.len() == self.maps.len() {
+                // gone round a cycle without finding `to`
+                return Err(missing());
+            }
+            path.push(map);
+            at = &map.to;
+        }
+        Ok(path)
+    }
+
+    /// The category at the end of the chain of maps from the seeds.
+    fn last_category(&self) -> &str {
+        let mut at = self.category.as_str();
+        for _ in 0..self.maps.len() {
+            match self.map_from(at) {
+                Some(map) => at = &map.to,
+                None => break,
+            }
+        }
+        at
+    }
+
+    /// Checks that the maps form one chain from the seeds' category, with
+    /// no overlapping ranges within a map.
+    fn validate(&self) -> Result<(), Vec<AlmanacError>> {
+        let mut errors = Vec::new();
+        for (i, map) in self.maps.iter().enumerate() {
+            if self.maps[..i].iter().any(|m| m.from == map.from) {
+                errors.push(AlmanacError::DuplicateFrom(map.from.clone()));
+            }
+            // `link` is sorted by source
+            for pair in map.link.windows(2) {
+                let (first, second) = (pair[0].source_interval(), pair[1].source_interval());
+                if second.start < first.end {
+                    errors.push(AlmanacError::Overlap {
+                        from: map.from.clone(),
+                        first,
+                        second,
+                    });
+                }
+            }
         }
 
-        let next_map = cur.to;
-        let this = self
+        let mut chain = vec![self.category.clone()];
+        while let Some(map) = self.map_from(chain.last().unwrap()) {
+            if let Some(i) = chain.iter().position(|c| *c == map.to) {
+                let mut cycle = chain[i..].to_vec();
+                cycle.push(map.to.clone());
+                errors.push(AlmanacError::Cycle(cycle));
+                break;
+            }
+            chain.push(map.to.clone());
+        }
+        // maps the chain never reaches are cut off from the seeds
+        let mut unreachable = self
             .maps
             .iter()
-            .find(|Map { from, .. }| from == &next_map)?;
-        self.traverse(to, next_value, this)
+            .map(|map| &map.from)
+            .filter(|from| !chain.contains(from))
+            .collect::<Vec<_>>();
+        unreachable.dedup();
+        for from in unreachable {
+            errors.push(AlmanacError::MissingLink {
+                from: self.category.clone(),
+                to: from.clone(),
+            });
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn traverse_from(&self, to: &str, start: usize, from: &str) -> Result<usize, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(start, |value, map| map.get(value)))
+    }
+
+    /// The maps from `from` to `to` fused into one.
+    pub fn compose(&self, from: &str, to: &str) -> Result<Piecewise, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(Piecewise::identity(), |composed, map| {
+                composed.then(&Piecewise::from_map(map))
+            }))
     }
 
-    pub fn traverse_from(&self, to: Attribute, start: usize, from: Attribute) -> Option<usize> {
-        let start_from = self.maps.iter().find(|Map { from: f, .. }| f == &from)?;
-        self.traverse(to, start, start_from)
+    /// Maps whole intervals of `from` values to the `to` values they end up
+    /// as.
+    pub fn traverse_intervals(
+        &self,
+        to: &str,
+        intervals: Vec<Interval>,
+        from: &str,
+    ) -> Result<Vec<Interval>, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(intervals, |intervals, map| {
+                intervals
+                    .iter()
+                    .flat_map(|interval| map.get_interval(interval))
+                    .collect()
+            }))
     }
 
     pub fn seeds(&self) -> impl Iterator<Item = &usize> + '_ {
@@ -148,17 +491,53 @@ impl Almanac {
             .flat_map(|SeedRange { start, end }| [start, end].into_iter())
     }
 
+    #[allow(dead_code)]
     pub fn seed_ranges(&self) -> impl Iterator<Item = usize> + '_ {
         self.seeds.iter().flat_map(|seed_range| seed_range.iter())
     }
 
-    // this is the faster way to search but rust finished the calculation
-    // while i was starting the reverse implementation
-    // pub fn invert(&mut self) {
-    //     self.maps.iter_mut().for_each(|&mut map| {
-    //         map.invert()
-    //     })
-    // }
+    /// Every `to` value that ends up as `value` of `from`, walking the maps
+    /// backwards. `to` comes before `from` in the chain.
+    #[allow(dead_code)]
+    pub fn invert_from(
+        &self,
+        to: &str,
+        value: usize,
+        from: &str,
+    ) -> Result<Vec<usize>, AlmanacError> {
+        let mut values = self
+            .path(to, from)?
+            .iter()
+            .rev()
+            .fold(vec![value], |values, map| {
+                values.iter().flat_map(|&value| map.invert(value)).collect()
+            });
+        values.sort_unstable();
+        values.dedup();
+        Ok(values)
+    }
+
+    /// Like `invert_from` for whole intervals, merged and sorted.
+    #[allow(dead_code)]
+    pub fn invert_intervals(
+        &self,
+        to: &str,
+        intervals: Vec<Interval>,
+        from: &str,
+    ) -> Result<Vec<Interval>, AlmanacError> {
+        Ok(self
+            .path(to, from)?
+            .iter()
+            .rev()
+            .fold(merge(intervals), |intervals, map| {
+                merge(
+                    intervals
+                        .iter()
+                        .flat_map(|interval| map.invert_interval(interval))
+                        .collect(),
+                )
+            }))
+    }
 }
 
 fn num(s: &str) -> IResult<&str, usize> {
@@ -166,23 +545,53 @@ fn num(s: &str) -> IResult<&str, usize> {
     map_res(digit1, str::parse)(next)
 }
 
+/// Where the chain of maps starts: the longest category a map reads from
+/// that the seeds' label starts with, so `seeds` finds `seed` and `glasses`
+/// finds `glass`. Otherwise the only category maps read from but never lead
+/// to, and failing that the label itself for `validate` to report.
+fn start_category<'a>(label: &'a str, maps: &'a [Map]) -> &'a str {
+    let named = maps
+        .iter()
+        .map(|map| map.from.as_str())
+        .filter(|from| label.starts_with(from))
+        .max_by_key(|from| from.len());
+    let roots = maps
+        .iter()
+        .map(|map| map.from.as_str())
+        .filter(|from| maps.iter().all(|map| map.to != *from))
+        .collect::<BTreeSet<_>>();
+    match (named, roots.len()) {
+        (Some(from), _) => from,
+        (None, 1) => roots.first().unwrap(),
+        _ => label,
+    }
+}
+
 impl Parse for Almanac {
     fn parse(s: &str) -> IResult<&str, Self> {
-        let (next, _) = tag("seeds: ")(s)?;
+        let (next, label) = terminated(category, tag(": "))(s)?;
         let (next, seeds) = many1(SeedRange::parse)(next)?;
         let (next, maps) = many1(Map::parse)(next)?;
-        Ok((next, Self { seeds, maps }))
+        let category = start_category(label, &maps).to_owned();
+        Ok((
+            next,
+            Self {
+                category,
+                seeds,
+                maps,
+            },
+        ))
     }
 }
 
 impl Parse for Map {
     fn parse(s: &str) -> IResult<&str, Map> {
         let (next, _) = take_till(|c: char| !c.is_whitespace())(s)?;
-        let (next, (from, to)) =
-            separated_pair(Attribute::parse, tag("-to-"), Attribute::parse)(next)?;
+        let (next, (from, to)) = separated_pair(category, tag("-to-"), category)(next)?;
+        let (from, to) = (from.to_owned(), to.to_owned());
         let (next, _) = take_till(|c: char| c.is_ascii_digit())(next)?;
-        let (next, ranges) = many1(Range::parse)(next)?;
-        dbg!(ranges.len());
+        let (next, mut ranges) = many1(Range::parse)(next)?;
+        ranges.sort_by_key(|range| range.source);
         Ok((
             next,
             Self {
@@ -195,21 +604,28 @@ impl Parse for Map {
 }
 
 fn part_one(s: &str) -> usize {
-    let (_, almanac) = Almanac::parse(s).unwrap();
+    let (_, almanac) = Almanac::parse(&normalize(s)).unwrap();
 
+    let seed_to_location = almanac
+        .compose(&almanac.category, almanac.last_category())
+        .unwrap();
     almanac
         .seeds()
-        .filter_map(|seed| almanac.traverse_from(Attribute::Location, *seed, Attribute::Seed))
+        .map(|&seed| seed_to_location.get(seed))
         .min()
         .unwrap()
 }
 
 fn part_two(s: &str) -> usize {
-    let (_, almanac) = Almanac::parse(s).unwrap();
+    let (_, almanac) = Almanac::parse(&normalize(s)).unwrap();
 
-        almanac
-        .seed_ranges()
-        .filter_map(|seed| almanac.traverse_from(Attribute::Location, seed, Attribute::Seed))
+    let seeds = almanac.seeds.iter().map(SeedRange::interval).collect();
+    almanac
+        .traverse_intervals(almanac.last_category(), seeds, &almanac.category)
+        .unwrap()
+        .iter()
+        .filter(|interval| !interval.is_empty())
+        .map(|interval| interval.start)
         .min()
         .unwrap()
 }
@@ -231,5 +647,254 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(include_str!("../test")), 46)
     }
-}
 
+    #[test]
+    fn test_part_two_answer() {
+        assert_eq!(part_two(include_str!("../input")), 69841803)
+    }
+
+    #[test]
+    fn test_intervals_match_values() {
+        let (_, almanac) = Almanac::parse(include_str!("../test")).unwrap();
+        let seeds = almanac.seeds.iter().map(SeedRange::interval).collect();
+        let mut mapped = almanac
+            .traverse_intervals("location", seeds, "seed")
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let mut expected = almanac
+            .seed_ranges()
+            .filter_map(|seed| almanac.traverse_from("location", seed, "seed").ok())
+            .collect::<Vec<_>>();
+        mapped.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(mapped, expected);
+    }
+
+    #[test]
+    fn test_split_interval() {
+        let map = Map {
+            link: vec![
+                Range {
+                    destination: 100,
+                    source: 10,
+                    range: 5,
+                },
+                Range {
+                    destination: 0,
+                    source: 20,
+                    range: 10,
+                },
+            ],
+            from: "seed".into(),
+            to: "soil".into(),
+        };
+        assert_eq!(
+            map.get_interval(&(0..40)),
+            [0..10, 100..105, 15..20, 0..10, 30..40]
+        );
+        assert_eq!(map.get_interval(&(12..22)), [102..105, 15..20, 0..2]);
+        assert_eq!(map.get_interval(&(16..18)), vec![16..18]);
+        assert_eq!(map.get_interval(&(5..5)), []);
+    }
+
+    #[test]
+    fn test_invert() {
+        let (_, almanac) = Almanac::parse(include_str!("../test")).unwrap();
+        let seeds_at = |location| almanac.invert_from("seed", location, "location").unwrap();
+        assert_eq!(seeds_at(46), [82]);
+        assert_eq!(seeds_at(35), [13]);
+        for location in 0..110 {
+            for seed in seeds_at(location) {
+                assert_eq!(
+                    almanac.traverse_from("location", seed, "seed"),
+                    Ok(location)
+                );
+            }
+        }
+
+        // the lowest location reachable from the seed ranges of part two
+        assert!(!reaches(&almanac, 0..46));
+        assert!(reaches(&almanac, 46..47));
+        let (_, almanac) = Almanac::parse(include_str!("../input")).unwrap();
+        assert!(!reaches(&almanac, 0..69841803));
+        assert!(reaches(&almanac, 69841803..69841804));
+    }
+
+    // whether any seed of part two lands somewhere in `locations`
+    fn reaches(almanac: &Almanac, locations: Interval) -> bool {
+        let seeds = merge(almanac.seeds.iter().map(SeedRange::interval).collect());
+        almanac
+            .invert_intervals("seed", vec![locations], "location")
+            .unwrap()
+            .iter()
+            .any(|s| {
+                seeds
+                    .iter()
+                    .any(|seed| s.start < seed.end && seed.start < s.end)
+            })
+    }
+
+    #[test]
+    fn test_invert_gaps() {
+        let map = Map {
+            link: vec![
+                Range {
+                    destination: 0,
+                    source: 10,
+                    range: 5,
+                },
+                Range {
+                    destination: 12,
+                    source: 20,
+                    range: 5,
+                },
+            ],
+            from: "seed".into(),
+            to: "soil".into(),
+        };
+        // 3 comes from 13 and from itself, 12 only from 20 since 12 itself
+        // maps to 2, and nothing maps to 22
+        assert_eq!(map.invert(3), [13, 3]);
+        assert_eq!(map.invert(12), [20]);
+        assert_eq!(map.invert(17), [17]);
+        assert_eq!(map.invert(22), []);
+        assert_eq!(merge(map.invert_interval(&(0..30))), vec![0..30]);
+        assert_eq!(merge(map.invert_interval(&(11..18))), [15..18, 20..25]);
+    }
+
+    #[test]
+    fn test_compose() {
+        let (_, almanac) = Almanac::parse(include_str!("../test")).unwrap();
+        let seed_to_soil = almanac.compose("seed", "soil").unwrap();
+        assert_eq!(
+            seed_to_soil.pieces,
+            [(0, 0), (50, 52), (98, 50), (100, 100)]
+        );
+        assert_eq!(
+            seed_to_soil.to_string(),
+            "0..50   -> 0..50\n50..98  -> 52..100\n98..100 -> 50..52\n100..   -> 100..\n"
+        );
+
+        let composed = almanac.compose("seed", "location").unwrap();
+        for seed in 0..200 {
+            assert_eq!(
+                Ok(composed.get(seed)),
+                almanac.traverse_from("location", seed, "seed")
+            );
+        }
+        let (_, almanac) = Almanac::parse(include_str!("../input")).unwrap();
+        let composed = almanac.compose("soil", "humidity").unwrap();
+        for (start, _) in composed.pieces.iter().take(50) {
+            for soil in [start.saturating_sub(1), *start, start + 1] {
+                assert_eq!(
+                    Ok(composed.get(soil)),
+                    almanac.traverse_from("humidity", soil, "soil")
+                );
+            }
+        }
+        assert_eq!(almanac.compose("seed", "seed"), Ok(Piecewise::identity()));
+        assert_eq!(
+            almanac.compose("location", "seed"),
+            Err(AlmanacError::MissingLink {
+                from: "location".into(),
+                to: "seed".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_categories() {
+        let input = "widgets: 5 2
+
+widget-to-gadget map:
+10 0 8
+
+gadget-to-gizmo map:
+0 14 2
+";
+        let (_, almanac) = Almanac::parse(input).unwrap();
+        assert_eq!(almanac.validate(), Ok(()));
+        assert_eq!(almanac.category, "widget");
+        assert_eq!(almanac.last_category(), "gizmo");
+        assert_eq!(almanac.traverse_from("gizmo", 5, "widget"), Ok(1));
+        assert_eq!(almanac.traverse_from("gadget", 5, "widget"), Ok(15));
+        assert_eq!(almanac.invert_from("gadget", 1, "gizmo"), Ok(vec![1, 15]));
+        assert_eq!(part_one(input), 1);
+        assert_eq!(part_two(input), 1);
+
+        let (_, almanac) = Almanac::parse(include_str!("../test")).unwrap();
+        assert_eq!(almanac.validate(), Ok(()));
+        assert_eq!(almanac.traverse_from("light", 81, "water"), Ok(74));
+
+        // not a plural with an `s` on the end
+        let input = "glasses: 1 2\n\nglass-to-cup map:\n5 0 10\n";
+        let (_, almanac) = Almanac::parse(input).unwrap();
+        assert_eq!(almanac.category, "glass");
+        assert_eq!(almanac.validate(), Ok(()));
+        assert_eq!(part_one(input), 6);
+        let input = "sheep: 1 2\n\nsheep-to-wool map:\n5 0 10\n\nwool-to-yarn map:\n0 0 1\n";
+        let (_, almanac) = Almanac::parse(input).unwrap();
+        assert_eq!(almanac.category, "sheep");
+        assert_eq!(almanac.last_category(), "yarn");
+        // a label naming no category falls back to where the chain starts
+        let input = "crops: 1 2\n\nflour-to-bread map:\n0 0 1\n\nwheat-to-flour map:\n0 0 1\n";
+        let (_, almanac) = Almanac::parse(input).unwrap();
+        assert_eq!(almanac.category, "wheat");
+    }
+
+    #[test]
+    fn test_validate() {
+        let errors = |input: &str| Almanac::parse(input).unwrap().1.validate().unwrap_err();
+        assert_eq!(
+            errors("seeds: 1 1\n\nseed-to-soil map:\n0 0 5\n10 3 5\n\nseed-to-dirt map:\n0 0 1\n"),
+            [
+                AlmanacError::Overlap {
+                    from: "seed".into(),
+                    first: 0..5,
+                    second: 3..8
+                },
+                AlmanacError::DuplicateFrom("seed".into()),
+            ]
+        );
+        assert_eq!(
+            errors("seeds: 1 1\n\nseed-to-soil map:\n0 0 1\n\nsoil-to-water map:\n0 0 1\n\nwater-to-soil map:\n0 0 1\n"),
+            [AlmanacError::Cycle(vec!["soil".into(), "water".into(), "soil".into()])]
+        );
+        let missing =
+            errors("seeds: 1 1\n\nseed-to-soil map:\n0 0 1\n\nwater-to-light map:\n0 0 1\n");
+        assert_eq!(
+            missing,
+            [AlmanacError::MissingLink {
+                from: "seed".into(),
+                to: "water".into()
+            }]
+        );
+        assert_eq!(missing[0].to_string(), "no maps lead from seed to water");
+
+        // every problem is reported, not just the first
+        assert_eq!(
+            errors("seeds: 1 1\n\nseed-to-soil map:\n0 0 1\n\nsoil-to-seed map:\n0 0 1\n\nwater-to-light map:\n0 0 1\n\nheat-to-cold map:\n0 0 1\n"),
+            [
+                AlmanacError::Cycle(vec!["seed".into(), "soil".into(), "seed".into()]),
+                AlmanacError::MissingLink {
+                    from: "seed".into(),
+                    to: "water".into()
+                },
+                AlmanacError::MissingLink {
+                    from: "seed".into(),
+                    to: "heat".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 35);
+            assert_eq!(part_two(&input), 46);
+        }
+    }
+}
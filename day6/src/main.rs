@@ -1,5 +1,6 @@
-use std::iter::zip;
+use std::{iter::zip, ops::Range};
 
+use common::{cli::Args, input::normalize};
 use nom::{
     bytes::complete::{tag, take_till, take_while},
     character::complete::digit1,
@@ -9,9 +10,26 @@ use nom::{
 };
 use std::fmt::Write;
 
+/// `--model=linear:RATE`, `--model=capped:RATE:MAX` or
+/// `--model=decaying:RATE:DECAY` prints the wins and best hold time of every
+/// part one race and the part two race under that model instead of the
+/// answers.
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let (args, flags) = Args::parse_with(&["--model"]);
+    let input = args.read_input(include_str!("../input"));
+    if let Some(flag) = flags.last() {
+        match flag.strip_prefix("--model=").and_then(parse_model) {
+            Some(model) => print!("{}", report(&input, model.as_ref())),
+            None => {
+                eprintln!("unknown flag {flag}");
+                std::process::exit(2)
+            }
+        }
+        return;
+    }
+    let cache = args.cache(6, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 trait Parse: Sized {
@@ -23,32 +41,185 @@ struct Race {
     target_distance: usize,
 }
 
+/// How far the boat goes for a hold time. The searches rely on the distance
+/// rising then falling with the hold time, as it does when speed only grows
+/// with charge and every millisecond of running adds to the distance.
+trait ChargeModel {
+    fn distance(&self, hold_time: usize, race_time: usize) -> u128;
+
+    // the peak is the first hold time that goes no further than the next one
+    fn best_hold(&self, race_time: usize) -> usize {
+        let (mut lo, mut hi) = (0, race_time);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.distance(mid, race_time) < self.distance(mid + 1, race_time) {
+                true => lo = mid + 1,
+                false => hi = mid,
+            }
+        }
+        lo
+    }
+
+    // winning hold times form one run around the best hold time, so its ends
+    // are found by binary search either side
+    fn wins(&self, race: &Race) -> usize {
+        let beats = |hold_time| self.distance(hold_time, race.time) > race.target_distance as u128;
+        let best = self.best_hold(race.time);
+        if !beats(best) {
+            return 0;
+        }
+        let first = partition_point(0..best, |hold_time| !beats(hold_time));
+        let end = partition_point(best..race.time + 1, beats);
+        end - first
+    }
+}
+
+/// Speed is `rate` per millisecond held, the puzzle's model at rate 1.
+struct Linear {
+    rate: usize,
+}
+
+impl ChargeModel for Linear {
+    fn distance(&self, hold_time: usize, race_time: usize) -> u128 {
+        self.rate as u128 * hold_time as u128 * (race_time - hold_time) as u128
+    }
+
+    fn best_hold(&self, race_time: usize) -> usize {
+        race_time / 2
+    }
+
+    fn wins(&self, race: &Race) -> usize {
+        match self.rate {
+            0 => 0,
+            // rate * x > target exactly when x > target / rate
+            rate => Race {
+                time: race.time,
+                target_distance: race.target_distance / rate,
+            }
+            .wins(),
+        }
+    }
+}
+
+/// As `Linear`, but the boat never goes faster than `max`.
+struct Capped {
+    rate: usize,
+    max: usize,
+}
+
+impl ChargeModel for Capped {
+    fn distance(&self, hold_time: usize, race_time: usize) -> u128 {
+        let speed = (self.rate as u128 * hold_time as u128).min(self.max as u128);
+        speed * (race_time - hold_time) as u128
+    }
+}
+
+/// As `Linear`, but the boat loses `decay` speed every millisecond it runs.
+struct Decaying {
+    rate: usize,
+    decay: usize,
+}
+
+impl ChargeModel for Decaying {
+    fn distance(&self, hold_time: usize, race_time: usize) -> u128 {
+        let run_time = (race_time - hold_time) as u128;
+        let speed = self.rate as u128 * hold_time as u128;
+        let decay = self.decay as u128;
+        // the boat stops once its speed has decayed away
+        let moving = match decay {
+            0 => run_time,
+            _ => run_time.min(speed.div_ceil(decay)),
+        };
+        moving * speed - decay * moving * moving.saturating_sub(1) / 2
+    }
+}
+
+/// `linear:RATE`, `capped:RATE:MAX` or `decaying:RATE:DECAY`.
+fn parse_model(s: &str) -> Option<Box<dyn ChargeModel>> {
+    let mut parts = s.split(':');
+    let name = parts.next()?;
+    let numbers = parts
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<usize>>>()?;
+    match (name, numbers.as_slice()) {
+        ("linear", &[rate]) => Some(Box::new(Linear { rate })),
+        ("capped", &[rate, max]) => Some(Box::new(Capped { rate, max })),
+        ("decaying", &[rate, decay]) => Some(Box::new(Decaying { rate, decay })),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
 struct Solution {
-    expected_distance: usize,
+    expected_distance: u128,
     hold_time: usize,
     run_time: usize,
 }
 
 impl Solution {
-    fn new(hold_time: usize, race_time: usize) -> Solution {
-        let run_time = race_time - hold_time;
-        let expected_distance = run_time * hold_time;
+    fn new(hold_time: usize, race_time: usize, model: &impl ChargeModel) -> Solution {
         Solution {
-            expected_distance,
+            expected_distance: model.distance(hold_time, race_time),
             hold_time,
-            run_time,
+            run_time: race_time - hold_time,
         }
     }
 
     fn is_valid(&self, target_distance: usize) -> bool {
-        self.expected_distance > target_distance
+        self.expected_distance > target_distance as u128
     }
 }
 
+// first point in range where pred stops holding, pred must go from true to false
+fn partition_point(range: Range<usize>, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut lo, mut hi) = (range.start, range.end);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match pred(mid) {
+            true => lo = mid + 1,
+            false => hi = mid,
+        }
+    }
+    lo
+}
+
 impl Race {
-    fn solve(&self) -> Vec<Solution> {
-        (0..self.time)
-            .map(|hold_time| Solution::new(hold_time, self.time))
+    fn beats(&self, hold_time: u128) -> bool {
+        let time = self.time as u128;
+        hold_time <= time && hold_time * (time - hold_time) > self.target_distance as u128
+    }
+
+    // hold times strictly beating the record lie between the roots of
+    // h * (time - h) = target; isqrt can be off by one, so nudge onto the boundary
+    fn wins(&self) -> usize {
+        let time = self.time as u128;
+        let discriminant = match (time * time).checked_sub(4 * self.target_distance as u128) {
+            Some(d) => d,
+            None => return 0,
+        };
+        let mut first = (time - discriminant.isqrt()) / 2;
+        while first > 0 && self.beats(first - 1) {
+            first -= 1;
+        }
+        while first <= time / 2 && !self.beats(first) {
+            first += 1;
+        }
+        // winning hold times are symmetric about time / 2
+        match first <= time / 2 {
+            true => (time - 2 * first + 1) as usize,
+            false => 0,
+        }
+    }
+
+    fn wins_with(&self, model: &impl ChargeModel) -> usize {
+        model.wins(self)
+    }
+
+    // reference for `wins_with`, trying every hold time
+    #[allow(dead_code)]
+    fn solve(&self, model: &impl ChargeModel) -> Vec<Solution> {
+        (0..=self.time)
+            .map(|hold_time| Solution::new(hold_time, self.time, model))
             .filter(|solution| solution.is_valid(self.target_distance))
             .collect()
     }
@@ -98,15 +269,43 @@ impl Parse for Races {
 }
 
 fn part_one(s: &str) -> usize {
-    let (_, races) = Races::parse(s).unwrap();
-    races.inner.iter().map(|race| race.solve().len()).product()
+    let (_, races) = Races::parse(&normalize(s)).unwrap();
+    races
+        .inner
+        .iter()
+        .map(|race| race.wins_with(&Linear { rate: 1 }))
+        .product()
 }
 
 fn part_two(s: &str) -> usize {
-    let (_, mut races) = Races::parse(s).unwrap();
+    let (_, mut races) = Races::parse(&normalize(s)).unwrap();
     races.concat();
 
-    races.inner.iter().map(|race| race.solve().len()).sum()
+    races
+        .inner
+        .iter()
+        .map(|race| race.wins_with(&Linear { rate: 1 }))
+        .sum()
+}
+
+/// One line per race, the part one races then the part two race.
+fn report(s: &str, model: &dyn ChargeModel) -> String {
+    let (_, mut races) = Races::parse(&normalize(s)).unwrap();
+    let mut out = String::new();
+    let mut line = |label: String, race: &Race| {
+        let _ = writeln!(
+            out,
+            "{label}: {} wins, best hold {} ms",
+            model.wins(race),
+            model.best_hold(race.time)
+        );
+    };
+    for (i, race) in races.inner.iter().enumerate() {
+        line(format!("race {}", i + 1), race);
+    }
+    races.concat();
+    line("part two".to_owned(), &races.inner[0]);
+    out
 }
 
 #[cfg(test)]
@@ -121,5 +320,150 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(include_str!("../test")), 71503);
     }
-}
 
+    #[test]
+    fn test_report() {
+        let report = |model| {
+            super::report(
+                include_str!("../test"),
+                parse_model(model).unwrap().as_ref(),
+            )
+        };
+        assert_eq!(
+            report("linear:1"),
+            "race 1: 4 wins, best hold 3 ms
+race 2: 8 wins, best hold 7 ms
+race 3: 9 wins, best hold 15 ms
+part two: 71503 wins, best hold 35765 ms
+"
+        );
+        assert!(report("capped:1:3").starts_with("race 1: 2 wins, best hold 3 ms\n"));
+        assert!(parse_model("decaying:2:1").is_some());
+        assert!(parse_model("capped:1").is_none());
+        assert!(parse_model("linear:x").is_none());
+        assert!(parse_model("quadratic:1").is_none());
+    }
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(part_one(include_str!("../input")), 500346);
+        assert_eq!(part_two(include_str!("../input")), 42515755);
+    }
+
+    #[test]
+    fn test_wins_matches_enumeration() {
+        for time in 0..80 {
+            for target_distance in 0..=(time * time / 4 + 2) {
+                let race = Race {
+                    time,
+                    target_distance,
+                };
+                assert_eq!(
+                    race.wins(),
+                    race.solve(&Linear { rate: 1 }).len(),
+                    "{time} {target_distance}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_wins_boundaries() {
+        let wins = |time, target_distance| {
+            Race {
+                time,
+                target_distance,
+            }
+            .wins()
+        };
+        // 10 and 20 only tie the record
+        assert_eq!(wins(30, 200), 9);
+        assert_eq!(wins(30, 224), 1);
+        assert_eq!(wins(30, 225), 0);
+        assert_eq!(wins(0, 0), 0);
+        // big enough that the square overflows u64
+        assert_eq!(wins(6_000_000_000, 0), 5_999_999_999);
+        assert_eq!(wins(6_000_000_000, 9_000_000_000_000_000_000 - 1), 1);
+    }
+
+    fn check_model(model: &impl ChargeModel, name: &str) {
+        for time in 0..40 {
+            let furthest = (0..=time)
+                .map(|hold_time| model.distance(hold_time, time))
+                .max()
+                .unwrap();
+            assert_eq!(model.distance(model.best_hold(time), time), furthest);
+            for target_distance in (0..=furthest as usize + 1).step_by(3) {
+                let race = Race {
+                    time,
+                    target_distance,
+                };
+                assert_eq!(
+                    race.wins_with(model),
+                    race.solve(model).len(),
+                    "{name} {time} {target_distance}"
+                );
+            }
+        }
+    }
+
+    // only `distance`, so the default searches are used
+    struct Searched<M>(M);
+
+    impl<M: ChargeModel> ChargeModel for Searched<M> {
+        fn distance(&self, hold_time: usize, race_time: usize) -> u128 {
+            self.0.distance(hold_time, race_time)
+        }
+    }
+
+    // speed grows with the square of the hold time
+    struct Quadratic;
+
+    impl ChargeModel for Quadratic {
+        fn distance(&self, hold_time: usize, race_time: usize) -> u128 {
+            (hold_time * hold_time * (race_time - hold_time)) as u128
+        }
+    }
+
+    #[test]
+    fn test_models_match_enumeration() {
+        for rate in 0..4 {
+            check_model(&Linear { rate }, &format!("linear {rate}"));
+            check_model(&Searched(Linear { rate }), &format!("searched {rate}"));
+            for max in [0, 1, 5, 12, 40] {
+                check_model(&Capped { rate, max }, &format!("capped {rate} {max}"));
+            }
+            for decay in [0, 1, 2, 5] {
+                check_model(
+                    &Decaying { rate, decay },
+                    &format!("decaying {rate} {decay}"),
+                );
+            }
+        }
+        check_model(&Quadratic, "quadratic");
+        // the peak of h² (t - h) is at 2t / 3
+        assert_eq!(Quadratic.best_hold(30), 20);
+    }
+
+    #[test]
+    fn test_model_distances() {
+        let capped = Capped { rate: 2, max: 5 };
+        assert_eq!(capped.distance(2, 10), 4 * 8);
+        assert_eq!(capped.distance(4, 10), 5 * 6);
+        // 6 + 4 + 2, then stopped
+        let decaying = Decaying { rate: 2, decay: 2 };
+        assert_eq!(decaying.distance(3, 10), 12);
+        // 9 + 6, then out of time
+        let decaying = Decaying { rate: 3, decay: 3 };
+        assert_eq!(decaying.distance(3, 5), 15);
+        assert_eq!(Linear { rate: 3 }.distance(3, 5), 18);
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 288);
+            assert_eq!(part_two(&input), 71503);
+        }
+    }
+}
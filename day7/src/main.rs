@@ -1,10 +1,21 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
+use common::{
+    cli::{Args, EXPLAIN},
+    explain::Trace,
+    input::normalize,
+};
 use itertools::Itertools;
 
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let (args, _) = Args::parse_with(&[EXPLAIN]);
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(7, include_str!("main.rs"));
+    if let Some(format) = args.explain {
+        println!("{}", explain(&input).render(format));
+    }
+    args.answer(cache.get_or_solve(1, &input, part_one));
+    args.answer(cache.get_or_solve(2, &input, part_two));
 }
 
 struct Hand<'a> {
@@ -41,13 +52,13 @@ impl Eq for Hand<'_> {}
 
 impl PartialOrd for Hand<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cards.cmp(&other.cards))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Hand<'_> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        self.cards.cmp(&other.cards)
     }
 }
 
@@ -211,15 +222,39 @@ impl<'a> AllHands<'a> {
 }
 
 fn part_one(s: &str) -> usize {
-    let mut all_hands = AllHands::from_str(s, false).unwrap();
+    let s = normalize(s);
+    let mut all_hands = AllHands::from_str(&s, false).unwrap();
     all_hands.sum()
 }
 
 fn part_two(s: &str) -> usize {
-    let mut all_hands = AllHands::from_str(s, true).unwrap();
+    let s = normalize(s);
+    let mut all_hands = AllHands::from_str(&s, true).unwrap();
     all_hands.sum()
 }
 
+fn explain(s: &str) -> Trace {
+    let s = normalize(s);
+    let mut trace = Trace::new();
+    for (part, is_part_2) in [(1, false), (2, true)] {
+        let mut all_hands = AllHands::from_str(&s, is_part_2).unwrap();
+        all_hands.sort();
+        for (i, hand) in all_hands.inner.iter().enumerate() {
+            let rank = i + 1;
+            trace
+                .step(
+                    part,
+                    hand.cards.inner,
+                    format!("{:?}", hand.cards.hand_type()),
+                )
+                .detail("rank", rank)
+                .detail("bet", hand.bet)
+                .detail("winnings", rank * hand.bet);
+        }
+    }
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,5 +267,37 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(include_str!("../test2")), 5905)
     }
-}
 
+    #[test]
+    fn test_explain() {
+        let trace = explain(include_str!("../test"));
+        let part_one = trace
+            .steps()
+            .filter(|step| step.part() == 1)
+            .map(|step| (step.item(), step.verdict()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            part_one,
+            [
+                ("32T3K", "Pair"),
+                ("KTJJT", "TwoPair"),
+                ("KK677", "TwoPair"),
+                ("T55J5", "ThreeOfAKind"),
+                ("QQQJA", "ThreeOfAKind"),
+            ]
+        );
+        assert!(trace.to_text().contains(
+            "[part 2] KTJJT: FourOfAKind\n    rank: 5\n    bet: 220\n    winnings: 1100\n"
+        ));
+    }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 6440);
+        }
+        for input in common::input::variants(include_str!("../test2")) {
+            assert_eq!(part_two(&input), 5905);
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::borrow::Borrow;
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
+use common::{cli::Args, input::normalize};
 use nom::bytes::complete::{tag, take_till, take_while1};
 use nom::character::complete::alpha1;
 use nom::multi::many1;
@@ -10,8 +11,11 @@ use nom::IResult;
 use num::integer::lcm;
 
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(8, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 trait Parse: Sized {
@@ -190,14 +194,16 @@ impl<'a> NodesIterator<'a> {
 }
 
 fn part_one(s: &str) -> usize {
-    let out = Map::parse(s);
+    let s = normalize(s);
+    let out = Map::parse(&s);
     let (_, map) = out.unwrap();
     let start = map.nodes.get("AAA").unwrap();
     map.node_iter(start, part_one_is_end).count()
 }
 
 fn part_two(s: &str) -> usize {
-    let out = Map::parse(s);
+    let s = normalize(s);
+    let out = Map::parse(&s);
     let (_, map) = out.unwrap();
     map.nodes_iter(part_two_is_end).lcm()
 }
@@ -214,5 +220,15 @@ mod tests {
     fn part_two_example() {
         assert_eq!(part_two(include_str!("../test2")), 6)
     }
+
+    #[test]
+    fn input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 6);
+        }
+        for input in common::input::variants(include_str!("../test2")) {
+            assert_eq!(part_two(&input), 6);
+        }
+    }
 }
 
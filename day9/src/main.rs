@@ -1,8 +1,13 @@
 use std::str::FromStr;
 
+use common::{cli::Args, input::normalize};
+
 fn main() {
-    dbg!(part_one(include_str!("../input")));
-    dbg!(part_two(include_str!("../input")));
+    let args = Args::parse();
+    let input = args.read_input(include_str!("../input"));
+    let cache = args.cache(9, include_str!("main.rs"));
+    println!("{}", cache.get_or_solve(1, &input, part_one));
+    println!("{}", cache.get_or_solve(2, &input, part_two));
 }
 
 #[derive(Debug)]
@@ -135,7 +140,7 @@ impl FromStr for Board {
 }
 
 fn part_one(input: &str) -> i64 {
-    let mut board: Board = input.parse().unwrap();
+    let mut board: Board = normalize(input).parse().unwrap();
     board
         .histories
         .iter_mut()
@@ -144,7 +149,7 @@ fn part_one(input: &str) -> i64 {
 }
 
 fn part_two(input: &str) -> i64 {
-    let mut board: Board = input.parse().unwrap();
+    let mut board: Board = normalize(input).parse().unwrap();
     board
         .histories
         .iter_mut()
@@ -165,5 +170,13 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(include_str!("../test")), 2)
     }
+
+    #[test]
+    fn test_input_variants() {
+        for input in common::input::variants(include_str!("../test")) {
+            assert_eq!(part_one(&input), 114);
+            assert_eq!(part_two(&input), 2);
+        }
+    }
 }
 